@@ -11,6 +11,7 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"u4q_8wwjC8oi7rZFUs0U4",
     init: |_| {
         Skill::builder()
+            .set_title("Base64")
             .add_corpus(Base64Corpus)
             .add_preprocessor(EncodingPreprocessor::new([
                 RawEncoding::Base64,
@@ -6,6 +6,7 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"6uuGAB41Wm0UUKduj9xtA",
     init: |_| {
         Skill::builder()
+            .set_title("Bech32")
             .add_translator(Bech32Translator)
             .set_command(Bech32Command)
             .build()
@@ -9,6 +9,7 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"DAyoatvv8aj-BkVUo-18M",
     init: |_| {
         Skill::builder()
+            .set_title("BlurHash")
             .add_translator(BlurHashTranslator)
             .set_command(BlurHashCommand)
             .build()
@@ -19,6 +19,7 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"Bk4CKgQi8qhO3A0IBqK5t",
     init: |_| {
         Skill::builder()
+            .set_title("Calculator")
             .add_suggestions(CalcSuggestions)
             .add_preprocessor(TokenMerger::new(NumeralTokenParser))
             .add_preprocessor(TokenMerger::new(CalcTokenParser))
@@ -11,6 +11,7 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"vun91APowetSJO3KeWXk1",
     init: |_| {
         Skill::builder()
+            .set_title("Chitchat")
             .add_corpus(ChitchatCorpus)
             .add_translator(ChitchatTranslator)
             .set_command(ChitchatCommand)
@@ -14,6 +14,7 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"Aiz9EnzXBdZcghXc9pwf~",
     init: |_| {
         Skill::builder()
+            .set_title("Color Converter")
             .add_suggestions(ColorSuggestions)
             .add_preprocessor(TokenMerger::new(ColorTokenParser))
             .add_labeler(ColorLabeler)
@@ -13,9 +13,11 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"eFMyoR1Zoq5fF8XLpmfCc",
     init: |_| {
         Skill::builder()
+            .set_title("Compression")
             .add_corpus(CompressionCorpus)
             .add_preprocessor(EncodingPreprocessor::new(RawEncoding::all()))
             .add_suggestions(CompressionSuggestions)
+            .add_completions(CompressionCompletions)
             .add_translator(CompressionTranslator)
             .set_command(CompressionCommand)
             .build()
@@ -51,6 +53,18 @@ impl Suggestions for CompressionSuggestions {
     }
 }
 
+pub struct CompressionCompletions;
+
+impl Completions for CompressionCompletions {
+    fn completions(&self) -> Vec<String> {
+        ["decompress"]
+            .into_iter()
+            .chain(ENTRIES.iter().flat_map(|entry| entry.keywords).copied())
+            .map(|s| s.to_string())
+            .collect()
+    }
+}
+
 pub struct CompressionCorpus;
 
 impl Corpus for CompressionCorpus {
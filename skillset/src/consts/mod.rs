@@ -8,13 +8,15 @@ mod definition;
 use definition::DEFINITIONS;
 
 pub const ENTRY: SkillEntry = SkillEntry {
-    model_id: b"sAMlRNhLB1Ww9T21BEDMl",
+    model_id: b"plQYJVAEcxn~C4EITMXnM",
     init: |_| {
         Skill::builder()
+            .set_title("Constants")
             .add_corpus(ConstCorpus)
             .add_translator(ConstTranslator)
             .add_suggestions(ConstSuggestions)
             .set_command(ConstCommand)
+            .set_features(FeatureSet::ALL)
             .build()
     },
 };
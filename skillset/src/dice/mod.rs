@@ -21,6 +21,7 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"Zy6NC4m5byQvmMEha3jNT",
     init: |_| {
         Skill::builder()
+            .set_title("Dice")
             .add_corpus(DiceCorpus)
             .add_preprocessor(TokenMerger::new(NumeralTokenParser))
             .add_preprocessor(TokenMerger::new(DiceTokenParser))
@@ -79,6 +80,16 @@ impl Corpus for DiceCorpus {
             ]),
         ]
     }
+
+    fn augmentation(&self) -> Augmentation {
+        Augmentation {
+            typos: 0.5,
+            politeness: 0.5,
+            synonyms: 1.0,
+            synonym_groups: vec![vec!["dice".into(), "die".into()]],
+            ..Default::default()
+        }
+    }
 }
 
 struct DiceTokenParser;
@@ -9,8 +9,10 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"G_60F_6L4j14b9v4_~u2m",
     init: |_| {
         Skill::builder()
+            .set_title("Digest")
             .add_corpus(DigestCorpus)
             .add_suggestions(DigestSuggestions)
+            .add_completions(DigestCompletions)
             .add_translator(DigestTranslator)
             .set_command(DigestCommand)
             .build()
@@ -87,6 +89,18 @@ impl Suggestions for DigestSuggestions {
     }
 }
 
+pub struct DigestCompletions;
+
+impl Completions for DigestCompletions {
+    fn completions(&self) -> Vec<String> {
+        ENTRIES
+            .iter()
+            .flat_map(|entry| entry.keywords)
+            .map(|s| s.to_string())
+            .collect()
+    }
+}
+
 pub struct DigestTranslator;
 
 impl Translator for DigestTranslator {
@@ -7,6 +7,7 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"nCK0EVzqv9V0ZTStJRjQn",
     init: |_| {
         Skill::builder()
+            .set_title("Geo")
             .add_preprocessor(GeoPreprocessor)
             .add_translator(GeoTranslator)
             .set_command(GeoCommand)
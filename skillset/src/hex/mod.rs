@@ -11,6 +11,7 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"fdU0PXy1uNGYlypwQiiOK",
     init: |_| {
         Skill::builder()
+            .set_title("Hex")
             .add_corpus(HexCorpus)
             .add_preprocessor(EncodingPreprocessor::new([RawEncoding::Hex]))
             .add_suggestions(HexSuggestions)
@@ -7,6 +7,7 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"CuDh69d5nMW-WsESpz5mI",
     init: |_| {
         Skill::builder()
+            .set_title("IP Address Converter")
             .add_translator(IpTranslator)
             .set_command(IpCommand)
             .build()
@@ -10,6 +10,7 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"881SN07tdT529jbaAYLwX",
     init: |_| {
         Skill::builder()
+            .set_title("JWT Decoder")
             .add_translator(JwtTranslator)
             .set_command(JwtCommand)
             .build()
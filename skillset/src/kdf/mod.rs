@@ -11,8 +11,10 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"jZfCk5tCjLb5jW0oTzuQ5",
     init: |_| {
         Skill::builder()
+            .set_title("KDF")
             .add_corpus(KdfCorpus)
             .add_suggestions(KdfSuggestions)
+            .add_completions(KdfCompletions)
             .add_translator(KdfTranslator)
             .set_command(KdfCommand)
             .build()
@@ -41,6 +43,18 @@ impl Suggestions for KdfSuggestions {
     }
 }
 
+pub struct KdfCompletions;
+
+impl Completions for KdfCompletions {
+    fn completions(&self) -> Vec<String> {
+        ENTRIES
+            .iter()
+            .flat_map(|entry| entry.keywords)
+            .map(|s| s.to_string())
+            .collect()
+    }
+}
+
 pub struct KdfCorpus;
 
 impl Corpus for KdfCorpus {
@@ -10,6 +10,7 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"WKQjgQTbySg0_NOxuUHBD",
     init: |_| {
         Skill::builder()
+            .set_title("Lorem ipsum")
             .add_preprocessor(TokenMerger::new(NumeralTokenParser))
             .add_corpus(LipsumCorpus)
             .add_translator(LipsumTranslator)
@@ -6,6 +6,7 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"3jD5f89FcJbrsvFBB387r",
     init: |_| {
         Skill::builder()
+            .set_title("Msgpack Decoder")
             .add_preprocessor(EncodingPreprocessor::new(RawEncoding::all()))
             .add_translator(MsgpackTranslator)
             .set_command(MsgpackCommand)
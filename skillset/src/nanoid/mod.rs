@@ -11,6 +11,7 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"NfqhgPyWBObz85VWwk1z9",
     init: |_| {
         Skill::builder()
+            .set_title("NanoID Generator")
             .add_preprocessor(TokenMerger::new(NumeralTokenParser))
             .add_corpus(NanoIdCorpus)
             .add_suggestions(NanoIdSuggestions)
@@ -58,6 +59,15 @@ impl Corpus for NanoIdCorpus {
             .chain(["nanoid", "NanoID"].map(|name| tk!([name; "command"])))
             .collect()
     }
+
+    fn augmentation(&self) -> Augmentation {
+        Augmentation {
+            typos: 0.5,
+            casing: 0.5,
+            politeness: 0.5,
+            ..Default::default()
+        }
+    }
 }
 
 pub struct NanoIdSuggestions;
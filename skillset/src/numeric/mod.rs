@@ -11,6 +11,7 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"nSz49UpiDtQLWUfAUZEq1",
     init: |_| {
         Skill::builder()
+            .set_title("Base Converter")
             .add_labeler(NumericLabeler)
             .add_translator(NumericTranslator)
             .set_command(NumericCommand)
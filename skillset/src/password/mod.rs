@@ -8,6 +8,7 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"bVGwEx0QkZ7j3EbXF-VXo",
     init: |_| {
         Skill::builder()
+            .set_title("Password Generator")
             .add_corpus(PasswordCorpus)
             .add_translator(PasswordTranslator)
             .set_command(PasswordCommand)
@@ -29,6 +30,17 @@ impl Corpus for PasswordCorpus {
             .chain(["password", "pwgen"].map(|name| tk!([name; "command"])))
             .collect()
     }
+
+    fn augmentation(&self) -> Augmentation {
+        Augmentation {
+            typos: 0.5,
+            casing: 0.5,
+            politeness: 0.5,
+            synonyms: 1.0,
+            synonym_groups: vec![vec!["password".into(), "pwgen".into()]],
+            ..Default::default()
+        }
+    }
 }
 
 pub struct PasswordTranslator;
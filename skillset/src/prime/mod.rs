@@ -13,6 +13,7 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"L75ldGqiqKrWzRjR_z2GE",
     init: |_| {
         Skill::builder()
+            .set_title("Primality Test")
             .add_preprocessor(TokenMerger::new(NumeralTokenParser))
             .add_corpus(PrimeCorpus)
             .add_translator(PrimeTranslator)
@@ -83,6 +84,14 @@ impl Corpus for PrimeCorpus {
         .into_iter()
         .collect()
     }
+
+    fn augmentation(&self) -> Augmentation {
+        Augmentation {
+            casing: 0.5,
+            politeness: 0.5,
+            ..Default::default()
+        }
+    }
 }
 
 pub struct PrimeTranslator;
@@ -10,6 +10,7 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"9Kr7qeDGzvzR8ph-ZyuQm",
     init: |_| {
         Skill::builder()
+            .set_title("Punycode")
             .add_suggestions(PunycodeSuggestions)
             .add_translator(PunycodeTranslator)
             .set_command(PunycodeCommand)
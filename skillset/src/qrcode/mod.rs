@@ -9,6 +9,7 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"ReS7P~L0c45If7OxLEPob",
     init: |_| {
         Skill::builder()
+            .set_title("QRCode Generator")
             .add_suggestions(QrCodeSuggestions)
             .add_corpus(QrCodeCorpus)
             .add_translator(QrCodeTranslator)
@@ -59,6 +60,16 @@ impl Corpus for QrCodeCorpus {
         })
         .collect()
     }
+
+    fn augmentation(&self) -> Augmentation {
+        Augmentation {
+            casing: 0.5,
+            politeness: 0.5,
+            synonyms: 0.5,
+            synonym_groups: vec![vec!["QR".into(), "QRCode".into(), "qrcode".into()]],
+            ..Default::default()
+        }
+    }
 }
 
 pub struct QrCodeTranslator;
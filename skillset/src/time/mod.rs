@@ -15,9 +15,11 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"0YGXydP31~VzDB5ccos2c",
     init: |_| {
         Skill::builder()
+            .set_title("Timestamp Converter")
             .add_corpus(TimeCorpus)
             .add_preprocessor(TokenMerger::new(TimeTokenParser))
             .add_translator(TimeTranslator)
+            .add_completions(TimeCompletions)
             .set_command(TimeCommand)
             .build()
     },
@@ -61,6 +63,19 @@ impl Corpus for TimeCorpus {
     }
 }
 
+pub struct TimeCompletions;
+
+impl Completions for TimeCompletions {
+    fn completions(&self) -> Vec<String> {
+        ["now", "time"]
+            .into_iter()
+            .chain(ENTRIES.iter().flat_map(|entry| entry.keywords).copied())
+            .filter(|s| !s.contains(char::is_whitespace))
+            .map(|s| s.to_string())
+            .collect()
+    }
+}
+
 const TIMESTAMP_TOLERANCE_DAYS: i64 = 365 * 10;
 
 fn parse_datetime(exp: &str) -> Option<OffsetDateTime> {
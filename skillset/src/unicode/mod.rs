@@ -6,6 +6,7 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"kGNawrrhjv7zXXJ5XDqA9",
     init: |_| {
         Skill::builder()
+            .set_title("Unicode Decoder")
             .add_suggestions(UnicodeSuggestions)
             .add_translator(UnicodeTranslator)
             .set_command(UnicodeCommand)
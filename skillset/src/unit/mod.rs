@@ -21,11 +21,13 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"Yiqp~EYDEpENMd3_6wcvo",
     init: |_| {
         Skill::builder()
+            .set_title("Unit Converter")
             .add_preprocessor(TokenMerger::new(NumeralTokenParser))
             .add_preprocessor(UnitPreprocessor)
             .add_corpus(UnitCorpus)
             .add_translator(UnitTranslator)
             .add_suggestions(UnitSuggestions)
+            .add_completions(UnitCompletions)
             .set_command(UnitCommand)
             .build()
     },
@@ -53,6 +55,14 @@ impl Suggestions for UnitSuggestions {
     }
 }
 
+pub struct UnitCompletions;
+
+impl Completions for UnitCompletions {
+    fn completions(&self) -> Vec<String> {
+        symbol::symbol_names().collect()
+    }
+}
+
 struct UnitPreprocessor;
 
 impl Preprocessor for UnitPreprocessor {
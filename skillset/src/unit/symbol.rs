@@ -22,6 +22,10 @@ fn symbols() -> impl Iterator<Item = (Option<UnitPrefix>, BaseUnit, String)> {
     })
 }
 
+pub fn symbol_names() -> impl Iterator<Item = String> {
+    symbols().map(|(_, _, sym)| sym)
+}
+
 pub fn parse_symbol(s: &str) -> Option<(Option<UnitPrefix>, BaseUnit)> {
     symbols()
         .find(|(_, _, sym)| sym.eq_ignore_ascii_case(s))
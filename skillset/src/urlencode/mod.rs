@@ -5,6 +5,7 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"6uuGAB41Wm0UUKduj9xtA",
     init: |_| {
         Skill::builder()
+            .set_title("URL Encoding")
             .add_translator(UrlEncodeTranslator)
             .set_command(UrlEncodeCommand)
             .build()
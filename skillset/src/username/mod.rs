@@ -15,6 +15,7 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"0T4ltRczKmeGFfFHz6q0i",
     init: |_| {
         Skill::builder()
+            .set_title("Username Generator")
             .add_preprocessor(TokenMerger::new(NumeralTokenParser))
             .add_corpus(UsernameCorpus)
             .add_suggestions(UsernameSuggestions)
@@ -12,6 +12,7 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"mDJn48cOMqZM2PcWN~RQn",
     init: |_| {
         Skill::builder()
+            .set_title("UUID Generator")
             .add_preprocessor(TokenMerger::new(NumeralTokenParser))
             .add_labeler(UuidLabeler)
             .add_corpus(UuidCorpus)
@@ -6,6 +6,7 @@ pub const ENTRY: SkillEntry = SkillEntry {
     model_id: b"OfHH5YRaI_kKKk7e56tCZ",
     init: |env| {
         Skill::builder()
+            .set_title("Version Info")
             .add_corpus(VersionCorpus)
             .add_translator(VersionTranslator)
             .set_command(VersionCommand(env.clone()))
@@ -0,0 +1,98 @@
+#ifndef YOZUK_H
+#define YOZUK_H
+
+/* This file is generated by cbindgen. Do not edit manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+typedef struct Yozuk Yozuk;
+
+// An input stream passed to Yozuk.
+//
+// `media_type` may be NULL, in which case `application/octet-stream` is assumed.
+typedef struct YozukStream {
+  const uint8_t *data;
+  size_t len;
+  const char *media_type;
+} YozukStream;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Creates a new Yozuk instance.
+//
+// Returns NULL if the initialization fails.
+// The returned pointer must be released with `yozuk_free`.
+Yozuk *yozuk_new(void);
+
+// Destroys a Yozuk instance.
+//
+// # Safety
+//
+// `zuk` must be a pointer returned by `yozuk_new` or NULL.
+void yozuk_free(Yozuk *zuk);
+
+// Returns the commands generated from `input` as a JSON array.
+//
+// Returns NULL if any of the arguments is invalid or the call fails.
+// The returned string must be released with `yozuk_string_free`.
+//
+// # Safety
+//
+// `zuk` must be a valid Yozuk instance, `input` must be a NUL-terminated string
+// and `streams` must point to `streams_len` valid streams.
+char *yozuk_get_commands(const Yozuk *zuk,
+                         const char *input,
+                         const YozukStream *streams,
+                         size_t streams_len);
+
+// Runs `commands`, a JSON array returned by `yozuk_get_commands`,
+// and returns the result as a JSON object.
+//
+// `user` is an optional JSON object describing the user context.
+// Returns NULL if any of the arguments is invalid or the call fails.
+// The returned string must be released with `yozuk_string_free`.
+//
+// # Safety
+//
+// `zuk` must be a valid Yozuk instance, `commands` and `user` must be NUL-terminated strings or NULL
+// and `streams` must point to `streams_len` valid streams.
+char *yozuk_run_commands(const Yozuk *zuk,
+                         const char *commands,
+                         const YozukStream *streams,
+                         size_t streams_len,
+                         const char *user);
+
+// Returns up to `amount` suggestions for `input` as a JSON array.
+//
+// Random suggestions are returned if `input` is empty and no streams are given.
+// Returns NULL if any of the arguments is invalid or the call fails.
+// The returned string must be released with `yozuk_string_free`.
+//
+// # Safety
+//
+// `zuk` must be a valid Yozuk instance, `input` must be a NUL-terminated string
+// and `streams` must point to `streams_len` valid streams.
+char *yozuk_suggestions(const Yozuk *zuk,
+                        const char *input,
+                        const YozukStream *streams,
+                        size_t streams_len,
+                        size_t amount);
+
+// Releases a string returned by Yozuk.
+//
+// # Safety
+//
+// `s` must be a string returned by Yozuk or NULL.
+void yozuk_string_free(char *s);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* YOZUK_H */
@@ -0,0 +1,217 @@
+use serde_derive::Serialize;
+use std::ffi::{CStr, CString};
+use std::io::Cursor;
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::{ptr, slice};
+use yozuk::Yozuk;
+use yozuk_sdk::prelude::*;
+
+/// An input stream passed to Yozuk.
+///
+/// `media_type` may be NULL, in which case `application/octet-stream` is assumed.
+#[repr(C)]
+pub struct YozukStream {
+    pub data: *const u8,
+    pub len: usize,
+    pub media_type: *const c_char,
+}
+
+/// Creates a new Yozuk instance.
+///
+/// Returns NULL if the initialization fails.
+/// The returned pointer must be released with `yozuk_free`.
+#[no_mangle]
+pub extern "C" fn yozuk_new() -> *mut Yozuk {
+    catch(|| Box::into_raw(Box::new(Yozuk::builder().build())))
+}
+
+/// Destroys a Yozuk instance.
+///
+/// # Safety
+///
+/// `zuk` must be a pointer returned by `yozuk_new` or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn yozuk_free(zuk: *mut Yozuk) {
+    if !zuk.is_null() {
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| drop(Box::from_raw(zuk))));
+    }
+}
+
+/// Returns the commands generated from `input` as a JSON array.
+///
+/// Returns NULL if any of the arguments is invalid or the call fails.
+/// The returned string must be released with `yozuk_string_free`.
+///
+/// # Safety
+///
+/// `zuk` must be a valid Yozuk instance, `input` must be a NUL-terminated string
+/// and `streams` must point to `streams_len` valid streams.
+#[no_mangle]
+pub unsafe extern "C" fn yozuk_get_commands(
+    zuk: *const Yozuk,
+    input: *const c_char,
+    streams: *const YozukStream,
+    streams_len: usize,
+) -> *mut c_char {
+    catch(|| {
+        let (zuk, input, streams) = match (
+            zuk.as_ref(),
+            to_str(input),
+            input_streams(streams, streams_len),
+        ) {
+            (Some(zuk), Some(input), Some(streams)) => (zuk, input, streams),
+            _ => return ptr::null_mut(),
+        };
+        let tokens = Tokenizer::new().tokenize(input);
+        to_json(&zuk.get_commands(&tokens, &streams))
+    })
+}
+
+/// Runs `commands`, a JSON array returned by `yozuk_get_commands`,
+/// and returns the result as a JSON object.
+///
+/// `user` is an optional JSON object describing the user context.
+/// Returns NULL if any of the arguments is invalid or the call fails.
+/// The returned string must be released with `yozuk_string_free`.
+///
+/// # Safety
+///
+/// `zuk` must be a valid Yozuk instance, `commands` and `user` must be NUL-terminated strings or NULL
+/// and `streams` must point to `streams_len` valid streams.
+#[no_mangle]
+pub unsafe extern "C" fn yozuk_run_commands(
+    zuk: *const Yozuk,
+    commands: *const c_char,
+    streams: *const YozukStream,
+    streams_len: usize,
+    user: *const c_char,
+) -> *mut c_char {
+    catch(|| {
+        let (zuk, commands, mut streams) = match (
+            zuk.as_ref(),
+            to_str(commands).and_then(|commands| serde_json::from_str(commands).ok()),
+            input_streams(streams, streams_len),
+        ) {
+            (Some(zuk), Some(commands), Some(streams)) => (zuk, commands, streams),
+            _ => return ptr::null_mut(),
+        };
+        let user: Option<UserContext> = if user.is_null() {
+            None
+        } else {
+            match to_str(user).and_then(|user| serde_json::from_str(user).ok()) {
+                Some(user) => Some(user),
+                None => return ptr::null_mut(),
+            }
+        };
+        let result = match zuk.run_commands(commands, &mut streams, user.as_ref()) {
+            Ok(outputs) => JsonResult::Ok { outputs },
+            Err(outputs) => JsonResult::Fail { outputs },
+        };
+        to_json(&result)
+    })
+}
+
+/// Returns up to `amount` suggestions for `input` as a JSON array.
+///
+/// Random suggestions are returned if `input` is empty and no streams are given.
+/// Returns NULL if any of the arguments is invalid or the call fails.
+/// The returned string must be released with `yozuk_string_free`.
+///
+/// # Safety
+///
+/// `zuk` must be a valid Yozuk instance, `input` must be a NUL-terminated string
+/// and `streams` must point to `streams_len` valid streams.
+#[no_mangle]
+pub unsafe extern "C" fn yozuk_suggestions(
+    zuk: *const Yozuk,
+    input: *const c_char,
+    streams: *const YozukStream,
+    streams_len: usize,
+    amount: usize,
+) -> *mut c_char {
+    catch(|| {
+        let (zuk, input, streams) = match (
+            zuk.as_ref(),
+            to_str(input),
+            input_streams(streams, streams_len),
+        ) {
+            (Some(zuk), Some(input), Some(streams)) => (zuk, input, streams),
+            _ => return ptr::null_mut(),
+        };
+        let suggestions = if input.trim().is_empty() && streams.is_empty() {
+            zuk.random_suggestions(amount)
+        } else {
+            let tokens = Tokenizer::new().tokenize(input);
+            zuk.suggestions(&tokens, &streams, amount)
+        };
+        to_json(&suggestions)
+    })
+}
+
+/// Releases a string returned by Yozuk.
+///
+/// # Safety
+///
+/// `s` must be a string returned by Yozuk or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn yozuk_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+fn catch<T>(f: impl FnOnce() -> *mut T) -> *mut T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(ptr::null_mut())
+}
+
+unsafe fn to_str<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        None
+    } else {
+        CStr::from_ptr(s).to_str().ok()
+    }
+}
+
+unsafe fn input_streams(streams: *const YozukStream, len: usize) -> Option<Vec<InputStream>> {
+    if len == 0 {
+        return Some(vec![]);
+    }
+    if streams.is_null() {
+        return None;
+    }
+    slice::from_raw_parts(streams, len)
+        .iter()
+        .map(|stream| {
+            let data = if stream.len == 0 {
+                vec![]
+            } else if stream.data.is_null() {
+                return None;
+            } else {
+                slice::from_raw_parts(stream.data, stream.len).to_vec()
+            };
+            let media_type = if stream.media_type.is_null() {
+                media_type!(APPLICATION / OCTET_STREAM).into()
+            } else {
+                MediaTypeBuf::from_string(to_str(stream.media_type)?.to_string()).ok()?
+            };
+            let mut stream = InputStream::new(Cursor::new(data), media_type);
+            stream.read_header().ok()?;
+            Some(stream)
+        })
+        .collect()
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> *mut c_char {
+    serde_json::to_string(value)
+        .ok()
+        .and_then(|json| CString::new(json).ok())
+        .map_or(ptr::null_mut(), CString::into_raw)
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum JsonResult {
+    Ok { outputs: Vec<Output> },
+    Fail { outputs: Vec<Output> },
+}
@@ -0,0 +1,134 @@
+use std::path::Path;
+
+// Declarations as cbindgen emits them: doc lines, then the signature.
+fn rust_declarations(source: &str) -> Vec<(Vec<String>, String)> {
+    let mut decls = Vec::new();
+    let mut docs = Vec::new();
+    let mut lines = source.lines();
+    while let Some(line) = lines.next() {
+        if let Some(doc) = line.strip_prefix("///") {
+            docs.push(doc.trim_start().to_string());
+        } else if line.contains("extern \"C\" fn ") || line == "pub struct YozukStream {" {
+            let mut signature = line.to_string();
+            while !signature.ends_with('{') {
+                signature.push_str(lines.next().unwrap().trim());
+            }
+            decls.push((docs.split_off(0), c_signature(&signature)));
+        } else if !line.starts_with("#[") {
+            docs.clear();
+        }
+    }
+    decls
+}
+
+fn c_signature(signature: &str) -> String {
+    if signature.starts_with("pub struct") {
+        return "struct YozukStream".into();
+    }
+    let (head, ret) = signature.trim_end_matches(" {").split_once(')').unwrap();
+    let (name, params) = head.split_once('(').unwrap();
+    let name = name.rsplit(' ').next().unwrap();
+    let params = params
+        .split(',')
+        .map(str::trim)
+        .filter(|param| !param.is_empty())
+        .map(|param| {
+            let (name, ty) = param.split_once(": ").unwrap();
+            format!("{}{}", c_type(ty), name)
+        })
+        .collect::<Vec<_>>();
+    let ret = ret
+        .trim()
+        .strip_prefix("-> ")
+        .map_or("void ".into(), c_type);
+    let params = if params.is_empty() {
+        "void".into()
+    } else {
+        params.join(", ")
+    };
+    format!("{}{}({})", ret, name, params)
+}
+
+fn c_type(ty: &str) -> String {
+    let (prefix, ty) = if let Some(ty) = ty.strip_prefix("*const ") {
+        ("const ", ty)
+    } else if let Some(ty) = ty.strip_prefix("*mut ") {
+        ("", ty)
+    } else {
+        return match ty {
+            "usize" => "size_t ".into(),
+            _ => panic!("unknown type: {}", ty),
+        };
+    };
+    let ty = match ty {
+        "c_char" => "char",
+        "u8" => "uint8_t",
+        ty => ty,
+    };
+    format!("{}{} *", prefix, ty)
+}
+
+fn header_declarations(header: &str) -> Vec<(Vec<String>, String)> {
+    let mut decls = Vec::new();
+    let mut docs = Vec::new();
+    let mut lines = header.lines();
+    while let Some(line) = lines.next() {
+        if let Some(doc) = line.strip_prefix("//") {
+            docs.push(doc.trim_start().to_string());
+        } else if line.starts_with("typedef struct YozukStream") {
+            decls.push((docs.split_off(0), "struct YozukStream".into()));
+        } else if line.contains('(') && !line.starts_with('#') {
+            let mut signature = line.to_string();
+            while !signature.ends_with(';') {
+                signature.push(' ');
+                signature.push_str(lines.next().unwrap().trim());
+            }
+            decls.push((
+                docs.split_off(0),
+                signature.trim_end_matches(';').to_string(),
+            ));
+        } else {
+            docs.clear();
+        }
+    }
+    decls
+}
+
+#[test]
+fn header_matches_source() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let source = std::fs::read_to_string(manifest_dir.join("src/lib.rs")).unwrap();
+    let header = std::fs::read_to_string(manifest_dir.join("include/yozuk.h")).unwrap();
+    assert_eq!(header_declarations(&header), rust_declarations(&source));
+}
+
+#[test]
+fn header_stream_fields() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let source = std::fs::read_to_string(manifest_dir.join("src/lib.rs")).unwrap();
+    let header = std::fs::read_to_string(manifest_dir.join("include/yozuk.h")).unwrap();
+
+    let fields = source
+        .split("pub struct YozukStream {")
+        .nth(1)
+        .and_then(|s| s.split('}').next())
+        .unwrap()
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("pub "))
+        .map(|field| {
+            let (name, ty) = field.trim_end_matches(',').split_once(": ").unwrap();
+            format!("{}{};", c_type(ty), name)
+        })
+        .collect::<Vec<_>>();
+    let header_fields = header
+        .split("typedef struct YozukStream {")
+        .nth(1)
+        .and_then(|s| s.split('}').next())
+        .unwrap()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    assert_eq!(header_fields, fields);
+}
@@ -0,0 +1,65 @@
+#include <assert.h>
+#include <stdio.h>
+#include <string.h>
+
+#include "yozuk.h"
+
+static void test_commands(const Yozuk *zuk) {
+  char *commands = yozuk_get_commands(zuk, "1 + 1", NULL, 0);
+  assert(commands != NULL);
+  assert(strstr(commands, "yozuk-skill-calc") != NULL);
+
+  char *result = yozuk_run_commands(zuk, commands, NULL, 0, "{\"locale\":\"en-US\"}");
+  assert(result != NULL);
+  assert(strstr(result, "\"type\":\"ok\"") != NULL);
+
+  yozuk_string_free(result);
+  yozuk_string_free(commands);
+}
+
+static void test_streams(const Yozuk *zuk) {
+  const char *text = "Hello World!";
+  YozukStream stream = {(const uint8_t *)text, strlen(text), "text/plain"};
+
+  char *commands = yozuk_get_commands(zuk, "md5", &stream, 1);
+  assert(commands != NULL);
+  assert(strstr(commands, "yozuk-skill-digest") != NULL);
+
+  char *result = yozuk_run_commands(zuk, commands, &stream, 1, NULL);
+  assert(result != NULL);
+  assert(strstr(result, "\"type\":\"ok\"") != NULL);
+
+  yozuk_string_free(result);
+  yozuk_string_free(commands);
+}
+
+static void test_suggestions(const Yozuk *zuk) {
+  char *suggestions = yozuk_suggestions(zuk, "", NULL, 0, 3);
+  assert(suggestions != NULL);
+  assert(suggestions[0] == '[');
+  yozuk_string_free(suggestions);
+}
+
+static void test_invalid(const Yozuk *zuk) {
+  assert(yozuk_run_commands(zuk, "not json", NULL, 0, NULL) == NULL);
+  assert(yozuk_get_commands(zuk, NULL, NULL, 0) == NULL);
+  assert(yozuk_get_commands(zuk, "1 + 1", NULL, 1) == NULL);
+
+  YozukStream stream = {NULL, 0, "invalid"};
+  assert(yozuk_suggestions(zuk, "", &stream, 1, 3) == NULL);
+}
+
+int main(void) {
+  Yozuk *zuk = yozuk_new();
+  assert(zuk != NULL);
+
+  test_commands(zuk);
+  test_streams(zuk);
+  test_suggestions(zuk);
+  test_invalid(zuk);
+
+  yozuk_free(zuk);
+  yozuk_string_free(NULL);
+  puts("ok");
+  return 0;
+}
@@ -0,0 +1,207 @@
+#![cfg(feature = "modelgen")]
+
+use std::iter;
+use yozuk_sdk::prelude::*;
+
+const POLITENESS_PREFIXES: &[&[&str]] = &[&["please"], &["could", "you"], &["can", "you"]];
+const QUESTION_PREFIXES: &[&[&str]] = &[&["what", "is"], &["how", "do", "I"], &["how", "to"]];
+const MIN_TYPO_LEN: usize = 4;
+
+pub(crate) fn augment(
+    tokens: Vec<Token>,
+    weight: f64,
+    augmentation: &Augmentation,
+) -> Vec<(Vec<Token>, f64)> {
+    let seed = fingerprint(&tokens);
+    let mut variants = Vec::new();
+
+    if augmentation.typos > 0.0 {
+        variants.extend(typo(&tokens, seed).map(|data| (data, augmentation.typos)));
+    }
+    if augmentation.casing > 0.0 {
+        variants.extend(casing(&tokens, seed).map(|data| (data, augmentation.casing)));
+    }
+    if augmentation.politeness > 0.0 {
+        let prefix = POLITENESS_PREFIXES[seed % POLITENESS_PREFIXES.len()];
+        variants.push((prepend(prefix, &tokens), augmentation.politeness));
+    }
+    if augmentation.questions > 0.0 {
+        let prefix = QUESTION_PREFIXES[seed % QUESTION_PREFIXES.len()];
+        variants.push((prepend(prefix, &tokens), augmentation.questions));
+    }
+    if augmentation.synonyms > 0.0 {
+        variants.extend(
+            synonyms(&tokens, &augmentation.synonym_groups)
+                .into_iter()
+                .map(|data| (data, augmentation.synonyms)),
+        );
+    }
+
+    let variants = variants
+        .into_iter()
+        .filter(|(data, _)| data != &tokens)
+        .map(|(data, ratio)| (data, weight * ratio))
+        .collect::<Vec<_>>();
+    iter::once((tokens, weight)).chain(variants).collect()
+}
+
+fn is_keyword(token: &Token) -> bool {
+    !token.tag.is_empty() && !token.tag.starts_with("input:")
+}
+
+fn is_word(token: &Token) -> bool {
+    !token.as_str().is_empty() && token.as_str().chars().all(|c| c.is_ascii_alphabetic())
+}
+
+fn fingerprint(tokens: &[Token]) -> usize {
+    tokens
+        .iter()
+        .flat_map(|token| token.data.iter())
+        .fold(0usize, |acc, &b| {
+            acc.wrapping_mul(31).wrapping_add(b as usize)
+        })
+}
+
+fn typo(tokens: &[Token], seed: usize) -> Option<Vec<Token>> {
+    let candidates = tokens
+        .iter()
+        .enumerate()
+        .filter(|(_, token)| {
+            token.tag.is_empty() && is_word(token) && token.as_str().len() >= MIN_TYPO_LEN
+        })
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    let index = *candidates.get(seed % candidates.len().max(1))?;
+
+    let mut chars = tokens[index].as_str().chars().collect::<Vec<_>>();
+    let pos = 1 + seed % (chars.len() - 2);
+    if seed.is_multiple_of(2) {
+        chars.swap(pos, pos + 1);
+    } else {
+        chars.remove(pos);
+    }
+
+    let mut data = tokens.to_vec();
+    data[index] = Token {
+        data: chars.into_iter().collect::<String>().into(),
+        ..tokens[index].clone()
+    };
+    Some(data)
+}
+
+fn casing(tokens: &[Token], seed: usize) -> Option<Vec<Token>> {
+    (seed..seed + 3)
+        .map(|mode| {
+            tokens
+                .iter()
+                .map(|token| {
+                    if is_word(token) && !token.tag.starts_with("input:") {
+                        Token {
+                            data: convert_case(token.as_str(), mode).into(),
+                            ..token.clone()
+                        }
+                    } else {
+                        token.clone()
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .find(|data| data != tokens)
+}
+
+fn convert_case(s: &str, mode: usize) -> String {
+    match mode % 3 {
+        0 => s.to_lowercase(),
+        1 => s.to_uppercase(),
+        _ => s[..1].to_uppercase() + &s[1..].to_lowercase(),
+    }
+}
+
+fn prepend(prefix: &[&'static str], tokens: &[Token]) -> Vec<Token> {
+    prefix
+        .iter()
+        .map(|word| tk!(*word))
+        .chain(tokens.iter().cloned())
+        .collect()
+}
+
+fn synonyms(tokens: &[Token], groups: &[Vec<String>]) -> Vec<Vec<Token>> {
+    tokens
+        .iter()
+        .enumerate()
+        .filter(|(_, token)| is_keyword(token))
+        .flat_map(|(index, token)| {
+            groups
+                .iter()
+                .filter(|group| group.iter().any(|word| word == token.as_str()))
+                .flatten()
+                .filter(move |word| word.as_str() != token.as_str())
+                .map(move |word| {
+                    let mut data = tokens.to_vec();
+                    data[index] = Token {
+                        data: word.clone().into(),
+                        ..token.clone()
+                    };
+                    data
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(sentences: &[(Vec<Token>, f64)]) -> Vec<(Vec<&str>, f64)> {
+        sentences
+            .iter()
+            .map(|(tokens, weight)| (tokens.iter().map(|token| token.as_str()).collect(), *weight))
+            .collect()
+    }
+
+    #[test]
+    fn test_augment() {
+        let tokens = tk!([
+            "generate",
+            "3"; "input:count",
+            "uuid"; "command"
+        ]);
+
+        assert_eq!(
+            words(&augment(tokens.clone(), 2.0, &Augmentation::default())),
+            [(vec!["generate", "3", "uuid"], 2.0)]
+        );
+
+        let augmentation = Augmentation {
+            politeness: 0.5,
+            synonyms: 0.25,
+            synonym_groups: vec![vec!["uuid".into(), "guid".into()]],
+            ..Default::default()
+        };
+        let sentences = augment(tokens.clone(), 2.0, &augmentation);
+        let sentences = words(&sentences);
+        assert_eq!(sentences.len(), 3);
+        assert_eq!(sentences[0], (vec!["generate", "3", "uuid"], 2.0));
+        assert_eq!(sentences[1].1, 1.0);
+        assert_eq!(
+            sentences[1].0[sentences[1].0.len() - 3..],
+            ["generate", "3", "uuid"]
+        );
+        assert_eq!(sentences[2], (vec!["generate", "3", "guid"], 0.5));
+
+        let augmentation = Augmentation {
+            typos: 1.0,
+            casing: 1.0,
+            ..Default::default()
+        };
+        let sentences = augment(tokens, 1.0, &augmentation);
+        assert_eq!(sentences.len(), 3);
+        for (data, _) in &sentences[1..] {
+            assert_eq!(data[1].as_str(), "3");
+            assert_eq!(data[2].tag, "command");
+        }
+        assert_ne!(sentences[1].0[0].as_str(), "generate");
+        assert_eq!(sentences[1].0[2].as_str(), "uuid");
+        assert_eq!(sentences[2].0[0].as_str().to_lowercase(), "generate");
+    }
+}
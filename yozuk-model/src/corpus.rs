@@ -0,0 +1,76 @@
+#![cfg(feature = "modelgen")]
+
+use super::augmentation::*;
+use anyhow::Result;
+use yozuk_sdk::prelude::*;
+
+#[derive(Debug, Clone)]
+pub struct TrainingSentence {
+    pub skill: String,
+    pub weight: f64,
+    pub tokens: Vec<Token>,
+}
+
+pub fn dump_corpus(
+    skills: &[NamedSkillEntry],
+    env: &Environment,
+    key: Option<&str>,
+) -> Result<Vec<TrainingSentence>> {
+    let mut sentences = Vec::new();
+    for item in skills
+        .iter()
+        .filter(|item| key.is_none_or(|key| key == item.key))
+    {
+        let skill = (item.entry.init)(env)?;
+        let augmented = augment_sentences(&skill, corpus_sentences(&skill));
+        sentences.extend(
+            augmented
+                .into_iter()
+                .map(|(tokens, weight)| TrainingSentence {
+                    skill: item.key.to_string(),
+                    weight,
+                    tokens: preprocess(&skill, tokens),
+                }),
+        );
+    }
+    Ok(sentences)
+}
+
+pub(crate) fn corpus_sentences(skill: &Skill) -> Vec<(Vec<Token>, usize)> {
+    skill
+        .corpora
+        .iter()
+        .enumerate()
+        .flat_map(|(index, corpus)| {
+            corpus
+                .training_data()
+                .into_iter()
+                .map(move |tokens| (tokens, index))
+        })
+        .collect()
+}
+
+pub(crate) fn augment_sentences<I>(skill: &Skill, sentences: I) -> Vec<(Vec<Token>, f64)>
+where
+    I: IntoIterator<Item = (Vec<Token>, usize)>,
+{
+    let corpora = skill
+        .corpora
+        .iter()
+        .map(|corpus| (corpus.weight(), corpus.augmentation()))
+        .collect::<Vec<_>>();
+    sentences
+        .into_iter()
+        .flat_map(|(tokens, index)| {
+            let (weight, augmentation) = &corpora[index];
+            augment(tokens, *weight, augmentation)
+        })
+        .collect()
+}
+
+pub(crate) fn preprocess(skill: &Skill, tokens: Vec<Token>) -> Vec<Token> {
+    skill
+        .preprocessors
+        .iter()
+        .fold(tokens, |tokens, prep| prep.preprocess(tokens))
+}
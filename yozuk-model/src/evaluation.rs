@@ -0,0 +1,491 @@
+#![cfg(feature = "modelgen")]
+
+use super::corpus::*;
+use super::labeler::*;
+use super::modelgen::*;
+use super::ood::*;
+use super::tagger::*;
+use anyhow::Result;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::{fmt, io};
+use yozuk_sdk::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Split {
+    HoldOut { test_ratio: f64 },
+    CrossValidation { folds: usize },
+}
+
+impl Split {
+    fn rounds(&self) -> usize {
+        match *self {
+            Self::HoldOut { .. } => 1,
+            Self::CrossValidation { folds } => folds.max(2),
+        }
+    }
+
+    /// Assigns each of `len` sentences to a fold. Round `n` tests fold `n`.
+    fn assign_folds(&self, len: usize, rng: &mut StdRng) -> Vec<usize> {
+        let mut folds: Vec<usize> = match *self {
+            Self::HoldOut { test_ratio } => {
+                let test = ((len as f64 * test_ratio).ceil() as usize).min(len);
+                (0..len).map(|index| usize::from(index >= test)).collect()
+            }
+            Self::CrossValidation { .. } => (0..len).map(|index| index % self.rounds()).collect(),
+        };
+        folds.shuffle(rng);
+        folds
+    }
+}
+
+impl Default for Split {
+    fn default() -> Self {
+        Self::HoldOut { test_ratio: 0.2 }
+    }
+}
+
+pub fn evaluate(
+    skills: &[NamedSkillEntry],
+    env: &Environment,
+    split: Split,
+    seed: u64,
+) -> Result<EvaluationReport> {
+    evaluate_inner(skills, env, split, seed, None)
+}
+
+pub fn evaluate_with_features(
+    skills: &[NamedSkillEntry],
+    env: &Environment,
+    split: Split,
+    seed: u64,
+    features: FeatureSet,
+) -> Result<EvaluationReport> {
+    evaluate_inner(skills, env, split, seed, Some(features))
+}
+
+fn evaluate_inner(
+    skills: &[NamedSkillEntry],
+    env: &Environment,
+    split: Split,
+    seed: u64,
+    features: Option<FeatureSet>,
+) -> Result<EvaluationReport> {
+    let mut skills = skills.to_vec();
+    skills.sort_by_key(|item| item.key);
+
+    let labelers = skills
+        .par_iter()
+        .flat_map(|item| (item.entry.init)(env).unwrap().labelers)
+        .collect::<Vec<_>>();
+
+    let labeler = FeatureLabeler::new(&labelers);
+
+    let skills = skills
+        .iter()
+        .map(|item| {
+            let mut skill = (item.entry.init)(env)?;
+            if let Some(features) = features {
+                skill.features = features;
+            }
+            Ok((item.key.to_string(), skill))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let corpora = skills
+        .iter()
+        .map(|(_, skill)| {
+            let sentences = corpus_sentences(skill);
+            let folds = split.assign_folds(sentences.len(), &mut rng);
+            sentences.into_iter().zip(folds).collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut report = EvaluationReport::new(skills.iter().map(|(key, _)| key.clone()));
+
+    for round in 0..split.rounds() {
+        let dataset = skills
+            .iter()
+            .zip(&corpora)
+            .map(|((key, skill), sentences)| {
+                let training = sentences
+                    .iter()
+                    .filter(|(_, fold)| *fold != round)
+                    .map(|(sentence, _)| sentence.clone());
+                let training = augment_sentences(skill, training);
+                (
+                    key.clone(),
+                    skill.features,
+                    preprocess_sentences(skill, training),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut models = train(&dataset, &labeler)
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+
+        let taggers = skills
+            .iter()
+            .map(|(key, _)| models.remove(key).map(|data| Tagger::new(data.into())))
+            .collect::<Vec<_>>();
+
+        let results = corpora
+            .par_iter()
+            .enumerate()
+            .flat_map(|(expected, sentences)| {
+                sentences
+                    .par_iter()
+                    .filter(|(_, fold)| *fold == round)
+                    .map(move |((tokens, _), _)| (expected, tokens))
+            })
+            .map(|(expected, tokens)| {
+                let tokens = tokens
+                    .iter()
+                    .map(|token| Token {
+                        tag: String::new(),
+                        ..token.clone()
+                    })
+                    .collect::<Vec<_>>();
+                (expected, predict(&skills, &taggers, &labeler, &tokens))
+            })
+            .collect::<Vec<_>>();
+
+        for (expected, predicted) in results {
+            report.add(expected, predicted);
+        }
+    }
+
+    Ok(report)
+}
+
+fn predict(
+    skills: &[(String, Skill)],
+    taggers: &[Option<Tagger>],
+    labeler: &FeatureLabeler,
+    tokens: &[Token],
+) -> Option<usize> {
+    let streams = [InputStream::new(
+        io::empty(),
+        media_type!(APPLICATION / OCTET_STREAM),
+    )];
+
+    let mut predicted: Option<(usize, i32)> = None;
+    for (index, ((_, skill), tagger)) in skills.iter().zip(taggers).enumerate() {
+        let command = if let Some(command) = &skill.command {
+            command
+        } else {
+            continue;
+        };
+        let tokens = skill
+            .preprocessors
+            .iter()
+            .fold(tokens.to_vec(), |tokens, prep| prep.preprocess(tokens));
+        let tokens = if let Some(tagger) = tagger {
+            tag_tokens(tagger, labeler, skill.features, tokens)
+        } else {
+            tokens
+        };
+        let matched = domain_confidence(&tokens) >= DEFAULT_OUT_OF_DOMAIN_THRESHOLD
+            && skill
+                .translators
+                .iter()
+                .any(|tr| tr.generate_command(&tokens, &streams).is_some());
+        if matched && predicted.is_none_or(|(_, priority)| command.priority() > priority) {
+            predicted = Some((index, command.priority()));
+        }
+    }
+
+    predicted.map(|(index, _)| index)
+}
+
+fn tag_tokens(
+    tagger: &Tagger,
+    labeler: &FeatureLabeler,
+    features: FeatureSet,
+    tokens: Vec<Token>,
+) -> Vec<Token> {
+    let features = labeler.label_features_with(&tokens, features);
+    let tags = tagger.tag(&features);
+    tokens
+        .into_iter()
+        .zip(tags)
+        .map(|(token, tag)| Token {
+            tag: if token.tag.is_empty() { tag } else { token.tag },
+            ..token
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvaluationReport {
+    keys: Vec<String>,
+    confusion: Vec<Vec<usize>>,
+}
+
+impl EvaluationReport {
+    fn new<I>(keys: I) -> Self
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let keys = keys.into_iter().collect::<Vec<_>>();
+        let confusion = vec![vec![0; keys.len() + 1]; keys.len()];
+        Self { keys, confusion }
+    }
+
+    fn add(&mut self, expected: usize, predicted: Option<usize>) {
+        let predicted = predicted.unwrap_or(self.keys.len());
+        self.confusion[expected][predicted] += 1;
+    }
+
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    pub fn total(&self) -> usize {
+        self.confusion.iter().flatten().sum()
+    }
+
+    pub fn correct(&self) -> usize {
+        (0..self.keys.len()).map(|i| self.confusion[i][i]).sum()
+    }
+
+    pub fn accuracy(&self) -> f64 {
+        ratio(self.correct(), self.total())
+    }
+
+    pub fn confusion(&self, expected: &str, predicted: Option<&str>) -> usize {
+        let expected = self.index(expected);
+        let predicted = match predicted {
+            Some(key) => self.index(key),
+            None => Some(self.keys.len()),
+        };
+        expected
+            .zip(predicted)
+            .map(|(expected, predicted)| self.confusion[expected][predicted])
+            .unwrap_or(0)
+    }
+
+    pub fn skills(&self) -> Vec<SkillReport> {
+        (0..self.keys.len())
+            .map(|i| {
+                let correct = self.confusion[i][i];
+                let support = self.confusion[i].iter().sum();
+                let predicted = self.confusion.iter().map(|row| row[i]).sum();
+                SkillReport {
+                    key: self.keys[i].clone(),
+                    precision: ratio(correct, predicted),
+                    recall: ratio(correct, support),
+                    support,
+                }
+            })
+            .collect()
+    }
+
+    pub fn misroutes(&self) -> Vec<(String, Option<String>, usize)> {
+        let mut misroutes = self
+            .confusion
+            .iter()
+            .enumerate()
+            .flat_map(|(expected, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(move |(predicted, count)| *predicted != expected && **count > 0)
+                    .map(move |(predicted, count)| {
+                        (
+                            self.keys[expected].clone(),
+                            self.keys.get(predicted).cloned(),
+                            *count,
+                        )
+                    })
+            })
+            .collect::<Vec<_>>();
+        misroutes.sort_by_key(|(_, _, count)| usize::MAX - count);
+        misroutes
+    }
+
+    fn index(&self, key: &str) -> Option<usize> {
+        self.keys.iter().position(|k| k == key)
+    }
+}
+
+impl fmt::Display for EvaluationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.keys.iter().map(|key| key.len()).max().unwrap_or(0);
+        writeln!(
+            f,
+            "{:width$}  {:>9}  {:>9}  {:>7}",
+            "skill", "precision", "recall", "support"
+        )?;
+        for skill in self.skills() {
+            writeln!(
+                f,
+                "{:width$}  {:>9.3}  {:>9.3}  {:>7}",
+                skill.key, skill.precision, skill.recall, skill.support
+            )?;
+        }
+        writeln!(
+            f,
+            "\naccuracy: {:.3} ({}/{})",
+            self.accuracy(),
+            self.correct(),
+            self.total()
+        )?;
+        let misroutes = self.misroutes();
+        if !misroutes.is_empty() {
+            writeln!(f, "\nmisroutes:")?;
+            for (expected, predicted, count) in misroutes {
+                writeln!(
+                    f,
+                    "{:width$}  ->  {:width$}  {:>7}",
+                    expected,
+                    predicted.as_deref().unwrap_or("(none)"),
+                    count
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkillReport {
+    pub key: String,
+    pub precision: f64,
+    pub recall: f64,
+    pub support: usize,
+}
+
+fn ratio(n: usize, d: usize) -> f64 {
+    if d == 0 {
+        0.0
+    } else {
+        n as f64 / d as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct KeywordCorpus(&'static [&'static str]);
+
+    impl Corpus for KeywordCorpus {
+        fn training_data(&self) -> Vec<Vec<Token>> {
+            self.0
+                .iter()
+                .flat_map(|keyword| {
+                    ["Hello", "World", "Yozuk", "Rust", "Lorem", "ipsum"]
+                        .into_iter()
+                        .map(move |data| tk!([data; "input:data", "to", *keyword; "keyword"]))
+                })
+                .collect()
+        }
+    }
+
+    struct KeywordTranslator(&'static [&'static str]);
+
+    impl Translator for KeywordTranslator {
+        fn generate_command(
+            &self,
+            args: &[Token],
+            _streams: &[InputStream],
+        ) -> Option<CommandArgs> {
+            if args
+                .iter()
+                .any(|arg| arg.tag == "keyword" && self.0.contains(&arg.as_str()))
+            {
+                Some(CommandArgs::new())
+            } else {
+                None
+            }
+        }
+    }
+
+    struct NopCommand;
+
+    impl Command for NopCommand {
+        fn run(
+            &self,
+            _args: CommandArgs,
+            _streams: &mut [InputStream],
+            _user: &UserContext,
+        ) -> Result<Output, CommandError> {
+            Ok(Output::new())
+        }
+    }
+
+    const UPPER_KEYWORDS: &[&str] = &["upper", "uppercase", "capital"];
+    const LOWER_KEYWORDS: &[&str] = &["lower", "lowercase", "small"];
+
+    const SKILLS: &[NamedSkillEntry] = &[
+        NamedSkillEntry {
+            key: "test-skill-upper",
+            entry: SkillEntry {
+                model_id: b"test-skill-upper",
+                init: |_| {
+                    Skill::builder()
+                        .add_corpus(KeywordCorpus(UPPER_KEYWORDS))
+                        .add_translator(KeywordTranslator(UPPER_KEYWORDS))
+                        .set_command(NopCommand)
+                        .build()
+                },
+            },
+        },
+        NamedSkillEntry {
+            key: "test-skill-lower",
+            entry: SkillEntry {
+                model_id: b"test-skill-lower",
+                init: |_| {
+                    Skill::builder()
+                        .add_corpus(KeywordCorpus(LOWER_KEYWORDS))
+                        .add_translator(KeywordTranslator(LOWER_KEYWORDS))
+                        .set_command(NopCommand)
+                        .build()
+                },
+            },
+        },
+    ];
+
+    #[test]
+    fn test_evaluation() {
+        let report = evaluate(
+            SKILLS,
+            &Environment::new(),
+            Split::CrossValidation { folds: 3 },
+            0,
+        )
+        .unwrap();
+
+        assert_eq!(report.keys(), ["test-skill-lower", "test-skill-upper"]);
+        assert_eq!(report.total(), 36);
+        assert_eq!(
+            report
+                .skills()
+                .iter()
+                .map(|s| s.support)
+                .collect::<Vec<_>>(),
+            [18, 18]
+        );
+        assert!(report.accuracy() >= 0.9, "{}", report);
+    }
+
+    #[test]
+    fn test_hold_out_ratio() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let split = Split::HoldOut { test_ratio: 0.3 };
+        for len in [0, 1, 10, 18, 100] {
+            let folds = split.assign_folds(len, &mut rng);
+            let test = folds.iter().filter(|fold| **fold == 0).count();
+            assert_eq!(folds.len(), len);
+            assert_eq!(test, (len as f64 * 0.3).ceil() as usize);
+        }
+
+        let report = evaluate(SKILLS, &Environment::new(), split, 0).unwrap();
+        assert_eq!(report.total(), 12);
+    }
+}
@@ -1,3 +1,4 @@
+use std::iter;
 use yozuk_helper_english::normalize;
 use yozuk_sdk::prelude::*;
 
@@ -6,6 +7,8 @@ use rayon::prelude::*;
 
 const MAXIMUM_SHANNON_ENTROPY: f32 = 3.0;
 const MAXIMUM_TOKEN_LENGTH: usize = 20;
+const MINIMUM_HEX_LENGTH: usize = 4;
+const NGRAM_SIZE: usize = 3;
 
 pub struct FeatureLabeler<'a> {
     labelers: &'a [Box<dyn Labeler>],
@@ -17,6 +20,10 @@ impl<'a> FeatureLabeler<'a> {
     }
 
     pub fn label_features(&self, input: &[Token]) -> Vec<Vec<Feature>> {
+        self.label_features_with(input, FeatureSet::default())
+    }
+
+    pub fn label_features_with(&self, input: &[Token], set: FeatureSet) -> Vec<Vec<Feature>> {
         #[cfg(feature = "rayon")]
         let iter = self.labelers.par_iter();
         #[cfg(not(feature = "rayon"))]
@@ -102,11 +109,99 @@ impl<'a> FeatureLabeler<'a> {
             }
         }
 
-        features
+        let features = features
             .into_iter()
             .zip(neighbors.into_iter())
             .map(|(a, b)| a.into_iter().chain(b.into_iter()).collect())
-            .collect()
+            .collect();
+
+        merge_features(features, extra_features(input, set))
+    }
+}
+
+fn extra_features(input: &[Token], set: FeatureSet) -> Vec<Vec<Feature>> {
+    let words = input
+        .iter()
+        .map(|token| {
+            if token.as_str().len() <= MAXIMUM_TOKEN_LENGTH {
+                normalize(token.as_str())
+            } else {
+                String::new()
+            }
+        })
+        .collect::<Vec<_>>();
+
+    (0..input.len())
+        .map(|i| {
+            let mut names = Vec::new();
+            if set.char_ngrams {
+                names.extend(char_ngrams(&words[i]).map(|ngram| format!("ngram:{}", ngram)));
+            }
+            if set.word_shape {
+                names.extend(word_shape(input[i].as_str()));
+            }
+            if set.position {
+                names.push(position(i, input.len()).into());
+            }
+            names
+                .into_iter()
+                .map(|name| Feature {
+                    name,
+                    ..Default::default()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn char_ngrams(word: &str) -> impl Iterator<Item = String> {
+    let chars = if word.is_empty() {
+        vec![]
+    } else {
+        iter::once('^')
+            .chain(word.chars())
+            .chain(iter::once('$'))
+            .collect::<Vec<_>>()
+    };
+    (0..chars.len().saturating_sub(NGRAM_SIZE - 1))
+        .map(move |i| chars[i..i + NGRAM_SIZE].iter().collect())
+}
+
+fn word_shape(word: &str) -> Vec<String> {
+    let mut shape = String::new();
+    for c in word.chars().take(MAXIMUM_TOKEN_LENGTH) {
+        let class = if c.is_uppercase() {
+            'X'
+        } else if c.is_lowercase() {
+            'x'
+        } else if c.is_ascii_digit() {
+            'd'
+        } else if c.is_alphanumeric() {
+            'a'
+        } else {
+            c
+        };
+        if !shape.ends_with(class) {
+            shape.push(class);
+        }
+    }
+
+    let mut shapes = vec![format!("shape:{}", shape)];
+    if word.len() >= MINIMUM_HEX_LENGTH
+        && word.chars().all(|c| c.is_ascii_hexdigit())
+        && word.chars().any(|c| c.is_ascii_digit())
+    {
+        shapes.push("shape:hex".into());
+    }
+    shapes
+}
+
+fn position(index: usize, len: usize) -> &'static str {
+    match (index, len) {
+        (_, 1) => "position:only",
+        (0, _) => "position:first",
+        (i, len) if i + 1 == len => "position:last",
+        _ => "position:middle",
     }
 }
 
@@ -122,3 +217,42 @@ fn merge_features(mut a: Vec<Vec<Feature>>, mut b: Vec<Vec<Feature>>) -> Vec<Vec
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(features: &[Feature]) -> Vec<&str> {
+        features.iter().map(|f| f.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_extra_features() {
+        let input = tk!(["Hello", "2beae68d"]);
+        assert_eq!(
+            extra_features(&input, FeatureSet::default()),
+            vec![vec![]; 2]
+        );
+
+        let features = extra_features(&input, FeatureSet::ALL);
+        assert_eq!(
+            names(&features[0]),
+            [
+                "ngram:^he",
+                "ngram:hel",
+                "ngram:ell",
+                "ngram:llo",
+                "ngram:lo$",
+                "shape:Xx",
+                "position:first",
+            ]
+        );
+        assert_eq!(
+            names(&features[1])
+                .into_iter()
+                .filter(|name| !name.starts_with("ngram:"))
+                .collect::<Vec<_>>(),
+            ["shape:dxdx", "shape:hex", "position:last",]
+        );
+    }
+}
@@ -0,0 +1,414 @@
+#![cfg(feature = "modelgen")]
+
+// Port of the L-BFGS solver and the More-Thuente line search from liblbfgs,
+// using the parameters CRFsuite passes to it by default.
+
+const MEMORIES: usize = 6;
+const EPSILON: f64 = 1e-5;
+const PERIOD: usize = 10;
+const DELTA: f64 = 1e-5;
+const MAX_LINESEARCH: usize = 20;
+const MIN_STEP: f64 = 1e-20;
+const MAX_STEP: f64 = 1e20;
+const FTOL: f64 = 1e-4;
+const GTOL: f64 = 0.9;
+const XTOL: f64 = 1e-16;
+
+pub(crate) fn minimize<F>(x: &mut [f64], mut evaluate: F) -> f64
+where
+    F: FnMut(&[f64], &mut [f64]) -> f64,
+{
+    let n = x.len();
+    let mut g = vec![0.0; n];
+    let mut fx = evaluate(x, &mut g);
+    if n == 0 {
+        return fx;
+    }
+
+    let mut pf = [0.0; PERIOD];
+    pf[0] = fx;
+
+    let mut d = g.iter().map(|v| -v).collect::<Vec<_>>();
+    if norm(&g) / norm(x).max(1.0) <= EPSILON {
+        return fx;
+    }
+
+    let mut s = vec![vec![0.0; n]; MEMORIES];
+    let mut y = vec![vec![0.0; n]; MEMORIES];
+    let mut ys = [0.0; MEMORIES];
+    let mut alpha = [0.0; MEMORIES];
+    let mut xp = vec![0.0; n];
+    let mut gp = vec![0.0; n];
+
+    let mut step = 1.0 / norm(&d);
+    let mut k = 1;
+    let mut end = 0;
+    loop {
+        xp.copy_from_slice(x);
+        gp.copy_from_slice(&g);
+
+        if line_search(x, &mut fx, &mut g, &d, &mut step, &xp, &mut evaluate).is_err() {
+            x.copy_from_slice(&xp);
+            return fx;
+        }
+
+        if norm(&g) / norm(x).max(1.0) <= EPSILON {
+            return fx;
+        }
+
+        if PERIOD <= k {
+            let rate = (pf[k % PERIOD] - fx) / fx;
+            if rate.abs() < DELTA {
+                return fx;
+            }
+        }
+        pf[k % PERIOD] = fx;
+
+        for i in 0..n {
+            s[end][i] = x[i] - xp[i];
+            y[end][i] = g[i] - gp[i];
+        }
+        let ys_end = dot(&y[end], &s[end]);
+        let yy = dot(&y[end], &y[end]);
+        ys[end] = ys_end;
+
+        let bound = MEMORIES.min(k);
+        k += 1;
+        end = (end + 1) % MEMORIES;
+
+        for (d, g) in d.iter_mut().zip(&g) {
+            *d = -g;
+        }
+
+        let mut j = end;
+        for _ in 0..bound {
+            j = (j + MEMORIES - 1) % MEMORIES;
+            alpha[j] = dot(&s[j], &d) / ys[j];
+            axpy(&mut d, &y[j], -alpha[j]);
+        }
+        for d in &mut d {
+            *d *= ys_end / yy;
+        }
+        for _ in 0..bound {
+            let beta = dot(&y[j], &d) / ys[j];
+            axpy(&mut d, &s[j], alpha[j] - beta);
+            j = (j + 1) % MEMORIES;
+        }
+
+        step = 1.0;
+    }
+}
+
+struct LineSearchError;
+
+fn line_search<F>(
+    x: &mut [f64],
+    f: &mut f64,
+    g: &mut [f64],
+    s: &[f64],
+    stp: &mut f64,
+    xp: &[f64],
+    evaluate: &mut F,
+) -> Result<(), LineSearchError>
+where
+    F: FnMut(&[f64], &mut [f64]) -> f64,
+{
+    if *stp <= 0.0 {
+        return Err(LineSearchError);
+    }
+
+    let dginit = dot(g, s);
+    if 0.0 < dginit {
+        return Err(LineSearchError);
+    }
+
+    let mut brackt = false;
+    let mut stage1 = true;
+    let mut uinfo = Ok(());
+    let finit = *f;
+    let dgtest = FTOL * dginit;
+    let mut width = MAX_STEP - MIN_STEP;
+    let mut prev_width = 2.0 * width;
+
+    let (mut stx, mut fx, mut dgx) = (0.0_f64, finit, dginit);
+    let (mut sty, mut fy, mut dgy) = (0.0, finit, dginit);
+
+    let mut count = 0;
+    loop {
+        let (stmin, stmax) = if brackt {
+            (stx.min(sty), stx.max(sty))
+        } else {
+            (stx, *stp + 4.0 * (*stp - stx))
+        };
+
+        *stp = stp.clamp(MIN_STEP, MAX_STEP);
+
+        if brackt
+            && ((*stp <= stmin || stmax <= *stp)
+                || MAX_LINESEARCH <= count + 1
+                || uinfo.is_err()
+                || stmax - stmin <= XTOL * stmax)
+        {
+            *stp = stx;
+        }
+
+        for ((x, xp), s) in x.iter_mut().zip(xp).zip(s) {
+            *x = xp + *stp * s;
+        }
+
+        *f = evaluate(x, g);
+        let dg = dot(g, s);
+        let ftest1 = finit + *stp * dgtest;
+        count += 1;
+
+        if brackt && ((*stp <= stmin || stmax <= *stp) || uinfo.is_err()) {
+            return Err(LineSearchError);
+        }
+        if *stp == MAX_STEP && *f <= ftest1 && dg <= dgtest {
+            return Err(LineSearchError);
+        }
+        if *stp == MIN_STEP && (ftest1 < *f || dgtest <= dg) {
+            return Err(LineSearchError);
+        }
+        if brackt && (stmax - stmin) <= XTOL * stmax {
+            return Err(LineSearchError);
+        }
+        if MAX_LINESEARCH <= count {
+            return Err(LineSearchError);
+        }
+        if *f <= ftest1 && dg.abs() <= GTOL * (-dginit) {
+            return Ok(());
+        }
+
+        if stage1 && *f <= ftest1 && FTOL.min(GTOL) * dginit <= dg {
+            stage1 = false;
+        }
+
+        if stage1 && ftest1 < *f && *f <= fx {
+            let fm = *f - *stp * dgtest;
+            let mut fxm = fx - stx * dgtest;
+            let mut fym = fy - sty * dgtest;
+            let dgm = dg - dgtest;
+            let mut dgxm = dgx - dgtest;
+            let mut dgym = dgy - dgtest;
+
+            uinfo = update_trial_interval(
+                (&mut stx, &mut fxm, &mut dgxm),
+                (&mut sty, &mut fym, &mut dgym),
+                (stp, fm, dgm),
+                (stmin, stmax),
+                &mut brackt,
+            );
+
+            fx = fxm + stx * dgtest;
+            fy = fym + sty * dgtest;
+            dgx = dgxm + dgtest;
+            dgy = dgym + dgtest;
+        } else {
+            uinfo = update_trial_interval(
+                (&mut stx, &mut fx, &mut dgx),
+                (&mut sty, &mut fy, &mut dgy),
+                (stp, *f, dg),
+                (stmin, stmax),
+                &mut brackt,
+            );
+        }
+
+        if brackt {
+            if 0.66 * prev_width <= (sty - stx).abs() {
+                *stp = stx + 0.5 * (sty - stx);
+            }
+            prev_width = width;
+            width = (sty - stx).abs();
+        }
+    }
+}
+
+fn update_trial_interval(
+    (x, fx, dx): (&mut f64, &mut f64, &mut f64),
+    (y, fy, dy): (&mut f64, &mut f64, &mut f64),
+    (t, ft, dt): (&mut f64, f64, f64),
+    (tmin, tmax): (f64, f64),
+    brackt: &mut bool,
+) -> Result<(), LineSearchError> {
+    let dsign = dt * (*dx / dx.abs()) < 0.0;
+
+    if *brackt {
+        if *t <= x.min(*y) || x.max(*y) <= *t {
+            return Err(LineSearchError);
+        }
+        if 0.0 <= *dx * (*t - *x) {
+            return Err(LineSearchError);
+        }
+        if tmax < tmin {
+            return Err(LineSearchError);
+        }
+    }
+
+    let bound;
+    let mut newt;
+    if *fx < ft {
+        *brackt = true;
+        bound = true;
+        let mc = cubic_minimizer(*x, *fx, *dx, *t, ft, dt);
+        let mq = quad_minimizer(*x, *fx, *dx, *t, ft);
+        newt = if (mc - *x).abs() < (mq - *x).abs() {
+            mc
+        } else {
+            mc + 0.5 * (mq - mc)
+        };
+    } else if dsign {
+        *brackt = true;
+        bound = false;
+        let mc = cubic_minimizer(*x, *fx, *dx, *t, ft, dt);
+        let mq = quad_minimizer2(*x, *dx, *t, dt);
+        newt = if (mc - *t).abs() > (mq - *t).abs() {
+            mc
+        } else {
+            mq
+        };
+    } else if dt.abs() < dx.abs() {
+        bound = true;
+        let mc = cubic_minimizer2(*x, *fx, *dx, *t, ft, dt, tmin, tmax);
+        let mq = quad_minimizer2(*x, *dx, *t, dt);
+        let closer = (*t - mc).abs() < (*t - mq).abs();
+        let farther = (*t - mc).abs() > (*t - mq).abs();
+        newt = if (*brackt && closer) || (!*brackt && farther) {
+            mc
+        } else {
+            mq
+        };
+    } else {
+        bound = false;
+        newt = if *brackt {
+            cubic_minimizer(*t, ft, dt, *y, *fy, *dy)
+        } else if *x < *t {
+            tmax
+        } else {
+            tmin
+        };
+    }
+
+    if *fx < ft {
+        *y = *t;
+        *fy = ft;
+        *dy = dt;
+    } else {
+        if dsign {
+            *y = *x;
+            *fy = *fx;
+            *dy = *dx;
+        }
+        *x = *t;
+        *fx = ft;
+        *dx = dt;
+    }
+
+    if tmax < newt {
+        newt = tmax;
+    }
+    if newt < tmin {
+        newt = tmin;
+    }
+
+    if *brackt && bound {
+        let mq = *x + 0.66 * (*y - *x);
+        if *x < *y {
+            if mq < newt {
+                newt = mq;
+            }
+        } else if newt < mq {
+            newt = mq;
+        }
+    }
+
+    *t = newt;
+    Ok(())
+}
+
+fn cubic_minimizer(u: f64, fu: f64, du: f64, v: f64, fv: f64, dv: f64) -> f64 {
+    let d = v - u;
+    let theta = (fu - fv) * 3.0 / d + du + dv;
+    let s = theta.abs().max(du.abs()).max(dv.abs());
+    let a = theta / s;
+    let mut gamma = s * (a * a - (du / s) * (dv / s)).sqrt();
+    if v < u {
+        gamma = -gamma;
+    }
+    let p = gamma - du + theta;
+    let q = gamma - du + gamma + dv;
+    u + p / q * d
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cubic_minimizer2(
+    u: f64,
+    fu: f64,
+    du: f64,
+    v: f64,
+    fv: f64,
+    dv: f64,
+    xmin: f64,
+    xmax: f64,
+) -> f64 {
+    let d = v - u;
+    let theta = (fu - fv) * 3.0 / d + du + dv;
+    let s = theta.abs().max(du.abs()).max(dv.abs());
+    let a = theta / s;
+    let mut gamma = s * (a * a - (du / s) * (dv / s)).max(0.0).sqrt();
+    if u < v {
+        gamma = -gamma;
+    }
+    let p = gamma - dv + theta;
+    let q = gamma - dv + gamma + du;
+    let r = p / q;
+    if r < 0.0 && gamma != 0.0 {
+        v - r * d
+    } else if d > 0.0 {
+        xmax
+    } else {
+        xmin
+    }
+}
+
+fn quad_minimizer(u: f64, fu: f64, du: f64, v: f64, fv: f64) -> f64 {
+    let a = v - u;
+    u + du / ((fu - fv) / a + du) / 2.0 * a
+}
+
+fn quad_minimizer2(u: f64, du: f64, v: f64, dv: f64) -> f64 {
+    let a = u - v;
+    v + dv / (dv - du) * a
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+fn norm(a: &[f64]) -> f64 {
+    dot(a, a).sqrt()
+}
+
+fn axpy(y: &mut [f64], x: &[f64], a: f64) {
+    for (y, x) in y.iter_mut().zip(x) {
+        *y += a * x;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minimize_rosenbrock() {
+        let mut x = [-1.2, 1.0];
+        minimize(&mut x, |x, g| {
+            let (a, b) = (x[0], x[1]);
+            g[0] = -2.0 * (1.0 - a) - 400.0 * a * (b - a * a);
+            g[1] = 200.0 * (b - a * a);
+            (1.0 - a).powi(2) + 100.0 * (b - a * a).powi(2)
+        });
+        assert!((x[0] - 1.0).abs() < 1e-3, "{:?}", x);
+        assert!((x[1] - 1.0).abs() < 1e-3, "{:?}", x);
+    }
+}
@@ -1,14 +1,29 @@
 #![forbid(unsafe_code)]
 #![deny(clippy::all)]
 
+mod augmentation;
+mod corpus;
+mod evaluation;
 mod labeler;
+mod lbfgs;
 mod modelgen;
+mod ood;
+mod size;
 mod tagger;
+mod trainer;
 
+#[cfg(feature = "modelgen")]
+pub use corpus::*;
+#[cfg(feature = "modelgen")]
+pub use evaluation::*;
 #[cfg(feature = "modelgen")]
 pub use modelgen::*;
+#[cfg(feature = "modelgen")]
+pub use trainer::*;
 
 pub use labeler::*;
+pub use ood::*;
+pub use size::*;
 pub use tagger::*;
 
 use yozuk_sdk::feature::Feature;
@@ -1,17 +1,70 @@
 #![cfg(feature = "modelgen")]
 
+use super::corpus::*;
 use super::labeler::*;
+use super::ood::*;
+use super::trainer::*;
 use anyhow::{bail, Result};
+use blake2::{digest::consts::U16, Blake2b, Digest};
 use bytes::Bytes;
-use crfsuite::{Algorithm, Attribute, GraphicalModel, Trainer};
+use crfs::Attribute;
 use itertools::multiunzip;
-use nanoid::nanoid;
 use rayon::prelude::*;
-use std::{collections::VecDeque, env, fs::File, io::Read, iter};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::{fs, iter};
 use yozuk_sdk::model::*;
 use yozuk_sdk::prelude::*;
 
+const CACHE_VERSION: &[u8] = b"yozuk-model-cache-4";
+
+#[derive(Debug, Clone, Default)]
+pub struct ModelgenOptions {
+    pub cache_dir: Option<PathBuf>,
+    pub prune_threshold: f64,
+    pub compress: bool,
+}
+
 pub fn modelgen(skills: &[NamedSkillEntry], env: &Environment) -> Result<ModelSet> {
+    modelgen_with_options(skills, env, &ModelgenOptions::default())
+}
+
+pub fn modelgen_cached(
+    skills: &[NamedSkillEntry],
+    env: &Environment,
+    cache_dir: &Path,
+) -> Result<ModelSet> {
+    modelgen_with_options(
+        skills,
+        env,
+        &ModelgenOptions {
+            cache_dir: Some(cache_dir.to_path_buf()),
+            ..Default::default()
+        },
+    )
+}
+
+pub fn modelgen_with_options(
+    skills: &[NamedSkillEntry],
+    env: &Environment,
+    options: &ModelgenOptions,
+) -> Result<ModelSet> {
+    if let Some(cache_dir) = &options.cache_dir {
+        fs::create_dir_all(cache_dir)?;
+    }
+    let model = generate(skills, env, options)?;
+    if options.compress {
+        model.compress()
+    } else {
+        Ok(model)
+    }
+}
+
+fn generate(
+    skills: &[NamedSkillEntry],
+    env: &Environment,
+    options: &ModelgenOptions,
+) -> Result<ModelSet> {
     let mut keys = skills
         .iter()
         .map(|item| item.key.to_string())
@@ -27,54 +80,224 @@ pub fn modelgen(skills: &[NamedSkillEntry], env: &Environment) -> Result<ModelSe
 
     let dataset = skills
         .par_iter()
-        .map(|item| TrainingData {
-            key: item.key.to_string(),
-            skills: vec![(item.entry.init)(env).unwrap()],
-            negative_skills: skills
-                .par_iter()
-                .filter(|neg| neg.key != item.key)
-                .map(|neg| (neg.entry.init)(env).unwrap())
-                .collect(),
+        .map(|item| {
+            let skill = (item.entry.init)(env).unwrap();
+            let sentences = augment_sentences(&skill, corpus_sentences(&skill));
+            (
+                item.key.to_string(),
+                skill.features,
+                preprocess_sentences(&skill, sentences),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let out_of_domain = options
+        .cache_dir
+        .as_ref()
+        .map(|_| sentences_digest(&out_of_domain_sentences(), &labeler));
+
+    let cache_files = skills
+        .par_iter()
+        .zip(&dataset)
+        .filter_map(|(item, (key, features, sentences))| {
+            let dir = options.cache_dir.as_ref()?;
+            let hash = cache_key(
+                item.entry.model_id,
+                *features,
+                options.prune_threshold,
+                &sentences_digest(sentences, &labeler),
+                out_of_domain.as_deref()?,
+            );
+            Some((key.clone(), dir.join(format!("{}-{}.crfsuite", key, hash))))
         })
-        .filter_map(|item| learn(item, &labeler).ok())
+        .collect::<HashMap<_, _>>();
+
+    if let Some(dir) = &options.cache_dir {
+        prune_cache(dir, &cache_files)?;
+    }
+
+    let mut cached = cache_files
+        .iter()
+        .filter_map(|(key, path)| fs::read(path).ok().map(|data| (key.clone(), data)))
         .collect::<Vec<_>>();
 
+    let trained = train_only(&dataset, &labeler, options.prune_threshold, |key| {
+        !cached.iter().any(|(cached, _)| cached == key)
+    });
+
+    for (key, data) in &trained {
+        if let Some(path) = cache_files.get(key) {
+            fs::write(path, data)?;
+        }
+    }
+
+    cached.extend(trained);
+    cached.sort_by_key(|(key, _)| dataset.iter().position(|(k, _, _)| k == key));
+
     let mut ranges = vec![0..0; keys.len()];
     let mut data = Vec::<u8>::new();
 
-    for (key, mut item) in dataset {
+    for (key, mut item) in cached {
         let index = keys.binary_search(&key).unwrap();
         ranges[index] = data.len()..data.len() + item.len();
         data.append(&mut item);
     }
 
-    Ok(ModelSet::new(
-        data,
-        keys.into_iter().zip(ranges.into_iter()),
-    ))
+    let sections = keys.into_iter().zip(ranges).map(|(key, range)| {
+        let features = dataset
+            .iter()
+            .find(|(k, _, _)| *k == key)
+            .map(|(_, features, _)| *features)
+            .unwrap_or_default();
+        (key, features, range)
+    });
+    Ok(ModelSet::new(data, sections))
+}
+
+fn cache_key(
+    model_id: &[u8],
+    features: FeatureSet,
+    prune_threshold: f64,
+    sentences_digest: &[u8],
+    out_of_domain_digest: &[u8],
+) -> String {
+    let mut hasher = Blake2b::<U16>::new();
+    hasher.update(CACHE_VERSION);
+    hasher.update((model_id.len() as u64).to_le_bytes());
+    hasher.update(model_id);
+    hasher.update([
+        features.char_ngrams as u8,
+        features.word_shape as u8,
+        features.position as u8,
+    ]);
+    hasher.update(prune_threshold.to_le_bytes());
+    hasher.update(sentences_digest);
+    hasher.update(out_of_domain_digest);
+    base64::encode_config(hasher.finalize(), base64::URL_SAFE_NO_PAD)
+}
+
+// A model is keyed on its own corpus and the labeler output for it, so editing
+// one skill only retrains that skill. The sentences of other skills are used
+// as negatives but deliberately left out of the key: cached models keep the
+// negatives they were trained with until the cache is cleared.
+fn sentences_digest(sentences: &[Vec<WeightedToken>], labeler: &FeatureLabeler) -> Vec<u8> {
+    let mut hasher = Blake2b::<U16>::new();
+    hasher.update((sentences.len() as u64).to_le_bytes());
+    for sentence in sentences {
+        hasher.update((sentence.len() as u64).to_le_bytes());
+        for token in sentence {
+            hasher.update((token.data.len() as u64).to_le_bytes());
+            hasher.update(&token.data);
+            hasher.update(token.media_type.as_str().as_bytes());
+            hasher.update((token.tag.len() as u64).to_le_bytes());
+            hasher.update(token.tag.as_bytes());
+            hasher.update(token.weight.to_le_bytes());
+        }
+        let tokens = sentence
+            .iter()
+            .map(|token| Token {
+                data: token.data.clone(),
+                tag: token.tag.clone(),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        for features in labeler.label_features_with(&tokens, FeatureSet::ALL) {
+            hasher.update((features.len() as u64).to_le_bytes());
+            for feature in features {
+                hasher.update([feature.non_entity as u8]);
+                let feature = feature.to_string();
+                hasher.update((feature.len() as u64).to_le_bytes());
+                hasher.update(feature.as_bytes());
+            }
+        }
+    }
+    hasher.finalize().to_vec()
+}
+
+fn prune_cache(dir: &Path, cache_files: &HashMap<String, PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_model = path.extension().is_some_and(|ext| ext == "crfsuite");
+        if is_model && !cache_files.values().any(|file| file == &path) {
+            fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+fn out_of_domain_sentences() -> Vec<Vec<WeightedToken>> {
+    let weight = OutOfDomainCorpus.weight();
+    OutOfDomainCorpus
+        .training_data()
+        .into_iter()
+        .map(|tokens| {
+            tokens
+                .into_iter()
+                .map(|token| WeightedToken::new(token, weight))
+                .collect()
+        })
+        .collect()
+}
+
+pub(crate) fn preprocess_sentences<I>(skill: &Skill, sentences: I) -> Vec<Vec<WeightedToken>>
+where
+    I: IntoIterator<Item = (Vec<Token>, f64)>,
+{
+    sentences
+        .into_iter()
+        .map(|(tokens, weight)| {
+            preprocess(skill, tokens)
+                .into_iter()
+                .map(|token| WeightedToken::new(token, weight))
+                .collect()
+        })
+        .collect()
+}
+
+pub(crate) fn train(
+    dataset: &[(String, FeatureSet, Vec<Vec<WeightedToken>>)],
+    labeler: &FeatureLabeler,
+) -> Vec<(String, Vec<u8>)> {
+    train_only(dataset, labeler, 0.0, |_| true)
+}
+
+fn train_only<F>(
+    dataset: &[(String, FeatureSet, Vec<Vec<WeightedToken>>)],
+    labeler: &FeatureLabeler,
+    prune_threshold: f64,
+    filter: F,
+) -> Vec<(String, Vec<u8>)>
+where
+    F: Fn(&str) -> bool + Sync,
+{
+    let out_of_domain = out_of_domain_sentences();
+    dataset
+        .par_iter()
+        .filter(|(key, _, _)| filter(key))
+        .map(|(key, features, sentences)| TrainingData {
+            key,
+            features: *features,
+            prune_threshold,
+            sentences,
+            negative_sentences: dataset
+                .iter()
+                .filter(|(neg, _, _)| neg != key)
+                .flat_map(|(_, _, sentences)| sentences)
+                .chain(&out_of_domain)
+                .collect(),
+        })
+        .filter_map(|item| learn(item, labeler).ok())
+        .collect()
 }
 
 fn learn(item: TrainingData, labeler: &FeatureLabeler) -> Result<(String, Vec<u8>)> {
-    let mut tr = Trainer::new(false);
-    tr.select(Algorithm::LBFGS, GraphicalModel::CRF1D).unwrap();
+    let mut tr = Trainer::new();
+    tr.set_prune_threshold(item.prune_threshold);
 
     let seq = item
-        .skills
+        .sentences
         .iter()
-        .map(|skill| (&skill.corpora, &skill.preprocessors))
-        .flat_map(|(corpora, preps)| {
-            corpora.iter().flat_map(move |corpus| {
-                let weight = corpus.weight();
-                corpus.training_data().into_iter().map(move |tokens| {
-                    preps
-                        .iter()
-                        .fold(tokens, |tokens, prep| prep.preprocess(tokens))
-                        .into_iter()
-                        .map(|token| WeightedToken::new(token, weight))
-                        .collect::<Vec<_>>()
-                })
-            })
-        })
+        .cloned()
         .flat_map(generate_wordiness)
         .map(|data| {
             let (yseq, words, weights): (Vec<_>, Vec<_>, Vec<_>) =
@@ -91,7 +314,7 @@ fn learn(item: TrainingData, labeler: &FeatureLabeler) -> Result<(String, Vec<u8
                 }));
 
             let xseq = labeler
-                .label_features(&words)
+                .label_features_with(&words, item.features)
                 .into_iter()
                 .zip(weights)
                 .map(|(features, weight)| {
@@ -111,26 +334,13 @@ fn learn(item: TrainingData, labeler: &FeatureLabeler) -> Result<(String, Vec<u8
     }
 
     for (xseq, yseq) in &seq {
-        tr.append(xseq, yseq, 0)?;
+        tr.append(xseq, yseq)?;
     }
 
     let seq = item
-        .negative_skills
-        .iter()
-        .map(|skill| (&skill.corpora, &skill.preprocessors))
-        .flat_map(|(corpora, preps)| {
-            corpora.iter().flat_map(move |corpus| {
-                let weight = corpus.weight();
-                corpus.training_data().into_iter().map(move |tokens| {
-                    preps
-                        .iter()
-                        .fold(tokens, |tokens, prep| prep.preprocess(tokens))
-                        .into_iter()
-                        .map(|token| WeightedToken::new(token, weight))
-                        .collect::<Vec<_>>()
-                })
-            })
-        })
+        .negative_sentences
+        .into_iter()
+        .cloned()
         .flat_map(generate_wordiness)
         .map(|data| {
             let (yseq, words, weights): (Vec<_>, Vec<_>, Vec<_>) =
@@ -139,7 +349,7 @@ fn learn(item: TrainingData, labeler: &FeatureLabeler) -> Result<(String, Vec<u8
                         if token.tag == "-" {
                             "-".to_string()
                         } else {
-                            "*".to_string()
+                            OUT_OF_DOMAIN_TAG.to_string()
                         },
                         Token {
                             data: token.data,
@@ -151,7 +361,7 @@ fn learn(item: TrainingData, labeler: &FeatureLabeler) -> Result<(String, Vec<u8
                 }));
 
             let xseq = labeler
-                .label_features(&words)
+                .label_features_with(&words, item.features)
                 .into_iter()
                 .zip(weights)
                 .map(|(features, weight)| {
@@ -168,27 +378,18 @@ fn learn(item: TrainingData, labeler: &FeatureLabeler) -> Result<(String, Vec<u8
         });
 
     for (xseq, yseq) in seq {
-        tr.append(&xseq, &yseq, 0)?;
+        tr.append(&xseq, &yseq)?;
     }
 
-    let filename = format!(
-        "{}/{}.crfsuite",
-        env::temp_dir().as_os_str().to_str().unwrap(),
-        nanoid!()
-    );
-
-    tr.train(&filename, -1)?;
-    let mut file = File::open(&filename)?;
-    let mut data = Vec::new();
-    file.read_to_end(&mut data)?;
-
-    Ok((item.key.to_string(), data))
+    Ok((item.key.to_string(), tr.train()?))
 }
 
-struct TrainingData {
-    key: String,
-    skills: Vec<Skill>,
-    negative_skills: Vec<Skill>,
+struct TrainingData<'a> {
+    key: &'a str,
+    features: FeatureSet,
+    prune_threshold: f64,
+    sentences: &'a [Vec<WeightedToken>],
+    negative_sentences: Vec<&'a Vec<WeightedToken>>,
 }
 
 fn generate_wordiness(data: Vec<WeightedToken>) -> impl Iterator<Item = Vec<WeightedToken>> {
@@ -257,3 +458,134 @@ impl From<Token> for WeightedToken {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct KeywordCorpus(&'static str);
+
+    impl Corpus for KeywordCorpus {
+        fn training_data(&self) -> Vec<Vec<Token>> {
+            ["Hello", "World", "Yozuk"]
+                .into_iter()
+                .map(|data| tk!([data; "input:data", "to", self.0; "keyword"]))
+                .collect()
+        }
+    }
+
+    const SKILLS: &[NamedSkillEntry] = &[
+        NamedSkillEntry {
+            key: "test-skill-upper",
+            entry: SkillEntry {
+                model_id: b"test-skill-upper",
+                init: |_| Skill::builder().add_corpus(KeywordCorpus("upper")).build(),
+            },
+        },
+        NamedSkillEntry {
+            key: "test-skill-lower",
+            entry: SkillEntry {
+                model_id: b"test-skill-lower",
+                init: |_| Skill::builder().add_corpus(KeywordCorpus("lower")).build(),
+            },
+        },
+    ];
+
+    fn model_bytes(model: &ModelSet) -> Vec<u8> {
+        let mut data = Vec::new();
+        model.write(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn test_modelgen_cached() {
+        let env = Environment::new();
+        let cache_dir = std::env::temp_dir().join(format!("yozuk-model-{}", std::process::id()));
+
+        let model = model_bytes(&modelgen(SKILLS, &env).unwrap());
+        let first = model_bytes(&modelgen_cached(SKILLS, &env, &cache_dir).unwrap());
+        assert_eq!(fs::read_dir(&cache_dir).unwrap().count(), 2);
+        fs::write(cache_dir.join("stale.crfsuite"), b"").unwrap();
+        let second = model_bytes(&modelgen_cached(SKILLS, &env, &cache_dir).unwrap());
+        assert_eq!(fs::read_dir(&cache_dir).unwrap().count(), 2);
+
+        let subset = modelgen_cached(&SKILLS[..1], &env, &cache_dir).unwrap();
+        assert_eq!(fs::read_dir(&cache_dir).unwrap().count(), 1);
+        fs::remove_dir_all(&cache_dir).unwrap();
+
+        assert_eq!(model, first);
+        assert_eq!(model, second);
+        assert_eq!(
+            subset.get(SKILLS[0].key),
+            ModelSet::from_data(first).unwrap().get(SKILLS[0].key)
+        );
+    }
+
+    #[test]
+    fn test_modelgen_cached_per_skill() {
+        const EDITED: &[NamedSkillEntry] = &[
+            SKILLS[0],
+            NamedSkillEntry {
+                key: "test-skill-lower",
+                entry: SkillEntry {
+                    model_id: b"test-skill-lower",
+                    init: |_| Skill::builder().add_corpus(KeywordCorpus("small")).build(),
+                },
+            },
+        ];
+
+        let env = Environment::new();
+        let cache_dir =
+            std::env::temp_dir().join(format!("yozuk-model-skill-{}", std::process::id()));
+        let cache_files = || {
+            let mut files = fs::read_dir(&cache_dir)
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name())
+                .collect::<Vec<_>>();
+            files.sort();
+            files
+        };
+
+        modelgen_cached(SKILLS, &env, &cache_dir).unwrap();
+        let before = cache_files();
+        modelgen_cached(EDITED, &env, &cache_dir).unwrap();
+        let after = cache_files();
+        fs::remove_dir_all(&cache_dir).unwrap();
+
+        let upper = |files: &[std::ffi::OsString]| {
+            files
+                .iter()
+                .find(|file| file.to_string_lossy().starts_with("test-skill-upper-"))
+                .cloned()
+        };
+        assert_eq!(after.len(), 2);
+        assert_eq!(upper(&before), upper(&after));
+        assert_ne!(before, after);
+    }
+
+    #[test]
+    fn test_modelgen_options() {
+        let env = Environment::new();
+        let model = modelgen(SKILLS, &env).unwrap();
+
+        let options = ModelgenOptions {
+            compress: true,
+            ..Default::default()
+        };
+        let compressed = modelgen_with_options(SKILLS, &env, &options).unwrap();
+        let compressed = ModelSet::from_data(model_bytes(&compressed)).unwrap();
+        assert!(compressed.is_compressed());
+        for (key, _) in model.stored_sizes() {
+            assert_eq!(compressed.get(key), model.get(key));
+        }
+
+        let options = ModelgenOptions {
+            prune_threshold: 1.0,
+            ..Default::default()
+        };
+        let pruned = modelgen_with_options(SKILLS, &env, &options).unwrap();
+        for ((_, size), (_, pruned)) in model.stored_sizes().zip(pruned.stored_sizes()) {
+            assert!(pruned < size);
+        }
+    }
+}
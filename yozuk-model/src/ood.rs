@@ -0,0 +1,150 @@
+use yozuk_sdk::prelude::*;
+
+pub const OUT_OF_DOMAIN_TAG: &str = "*";
+pub const DEFAULT_OUT_OF_DOMAIN_THRESHOLD: f64 = 0.5;
+
+pub fn domain_confidence(tokens: &[Token]) -> f64 {
+    if tokens.is_empty() {
+        return 1.0;
+    }
+    let in_domain = tokens
+        .iter()
+        .filter(|token| token.tag != OUT_OF_DOMAIN_TAG)
+        .count();
+    in_domain as f64 / tokens.len() as f64
+}
+
+pub struct OutOfDomainCorpus;
+
+impl Corpus for OutOfDomainCorpus {
+    fn training_data(&self) -> Vec<Vec<Token>> {
+        let tokenizer = Tokenizer::new();
+        OUT_OF_DOMAIN_SENTENCES
+            .iter()
+            .map(|sentence| tokenizer.tokenize(sentence))
+            .collect()
+    }
+}
+
+const OUT_OF_DOMAIN_SENTENCES: &[&str] = &[
+    "What is the weather like today",
+    "Will it rain tomorrow",
+    "Book a flight to Paris",
+    "Find a hotel near the airport",
+    "Who won the game last night",
+    "Play some music",
+    "Play the next song",
+    "Tell me a joke",
+    "How tall is Mount Everest",
+    "Remind me to buy milk",
+    "Add eggs to my shopping list",
+    "Translate good morning into French",
+    "Order a pizza",
+    "Set an alarm for seven",
+    "Wake me up early",
+    "Turn off the lights",
+    "Turn up the volume",
+    "Call mom",
+    "Send an email to Bob",
+    "Text Alice that I am running late",
+    "Open my calendar",
+    "Schedule a meeting with the team",
+    "What is on my agenda",
+    "Navigate to the nearest gas station",
+    "How long does it take to drive to work",
+    "Show me the traffic",
+    "Recommend a good movie",
+    "What should I cook for dinner",
+    "Give me a recipe for pancakes",
+    "How do I make coffee",
+    "Where is my phone",
+    "Buy a new laptop",
+    "Cancel my subscription",
+    "Track my package",
+    "What is the capital of Australia",
+    "Who wrote the book",
+    "Who is the president",
+    "When was the castle built",
+    "Why is the sky blue",
+    "How do airplanes fly",
+    "What does this word mean",
+    "Spell necessary",
+    "Read me the news",
+    "Show me the latest headlines",
+    "What is the stock price of the company",
+    "How is the market doing",
+    "Pay the electricity bill",
+    "Transfer money to my savings account",
+    "What is my balance",
+    "Book a table for two",
+    "Is the restaurant open",
+    "Find a dentist nearby",
+    "Make an appointment with the doctor",
+    "I have a headache",
+    "How many calories are in an apple",
+    "Start a workout",
+    "How many steps did I walk today",
+    "Take a picture",
+    "Show my photos from last summer",
+    "Post this on social media",
+    "Lock the front door",
+    "Is the garage door open",
+    "Set the thermostat to warm",
+    "Vacuum the living room",
+    "Water the plants",
+    "Feed the cat",
+    "Walk the dog",
+    "I am bored",
+    "I feel sad",
+    "Sing me a song",
+    "Tell me a story",
+    "Let's play a game",
+    "Do you like pizza",
+    "What is your favorite color",
+    "Are you a robot",
+    "Do you have feelings",
+    "I love you",
+    "Good night",
+    "See you later",
+    "That is not what I asked",
+    "You are wrong",
+    "Never mind",
+    "Stop",
+    "Cancel",
+    "Go back",
+    "Repeat that",
+    "Speak louder",
+    "Learn Spanish",
+    "Practice my vocabulary",
+    "Explain quantum physics",
+    "Summarize this article",
+    "Write a poem about the sea",
+    "Draft a cover letter",
+    "Fix my printer",
+    "Why is my computer slow",
+    "Update my phone",
+    "Connect to the wifi",
+    "Turn on bluetooth",
+    "Charge my car",
+    "Find a parking spot",
+    "Rent a bike",
+    "Call a taxi",
+    "When does the next train leave",
+    "Is my flight delayed",
+    "Check in for my flight",
+    "Pack my suitcase",
+    "What should I wear today",
+    "Do I need an umbrella",
+    "Plan a trip to Japan",
+    "What are the best places to visit in Rome",
+    "Show me pictures of cats",
+    "Find a gift for my sister",
+    "Wish my friend a happy birthday",
+    "Write a thank you note",
+    "Who is calling",
+    "Answer the phone",
+    "Ignore the call",
+    "Delete my voicemail",
+    "Clean my inbox",
+    "Mark all emails as read",
+];
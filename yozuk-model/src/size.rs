@@ -0,0 +1,72 @@
+use std::fmt;
+use yozuk_sdk::model::ModelSet;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeReport {
+    skills: Vec<SkillSize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkillSize {
+    pub key: String,
+    pub stored: usize,
+    pub uncompressed: usize,
+}
+
+impl SizeReport {
+    pub fn new(model: &ModelSet) -> Self {
+        let mut skills = model
+            .stored_sizes()
+            .map(|(key, stored)| SkillSize {
+                key: key.to_string(),
+                stored,
+                uncompressed: model.get(key).map(|data| data.len()).unwrap_or(0),
+            })
+            .collect::<Vec<_>>();
+        skills.sort_by(|a, b| b.stored.cmp(&a.stored).then(a.key.cmp(&b.key)));
+        Self { skills }
+    }
+
+    pub fn skills(&self) -> &[SkillSize] {
+        &self.skills
+    }
+
+    pub fn stored(&self) -> usize {
+        self.skills.iter().map(|skill| skill.stored).sum()
+    }
+
+    pub fn uncompressed(&self) -> usize {
+        self.skills.iter().map(|skill| skill.uncompressed).sum()
+    }
+}
+
+impl fmt::Display for SizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .skills
+            .iter()
+            .map(|skill| skill.key.len())
+            .max()
+            .unwrap_or(0)
+            .max("total".len());
+        writeln!(
+            f,
+            "{:width$}  {:>10}  {:>12}",
+            "skill", "stored", "uncompressed"
+        )?;
+        for skill in &self.skills {
+            writeln!(
+                f,
+                "{:width$}  {:>10}  {:>12}",
+                skill.key, skill.stored, skill.uncompressed
+            )?;
+        }
+        writeln!(
+            f,
+            "{:width$}  {:>10}  {:>12}",
+            "total",
+            self.stored(),
+            self.uncompressed()
+        )
+    }
+}
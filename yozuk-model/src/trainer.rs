@@ -0,0 +1,559 @@
+#![cfg(feature = "modelgen")]
+
+use super::lbfgs;
+use anyhow::{bail, Result};
+use cqdb::CQDBWriter;
+use crfs::Attribute;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Cursor, Seek, SeekFrom, Write};
+
+const C2: f64 = 1.0;
+
+const HEADER_SIZE: u64 = 48;
+const CHUNK_SIZE: u64 = 12;
+
+const STATE_FEATURE: u32 = 0;
+const TRANSITION_FEATURE: u32 = 1;
+
+pub struct Trainer {
+    labels: Dictionary,
+    attrs: Dictionary,
+    instances: Vec<Instance>,
+    prune_threshold: f64,
+}
+
+impl Trainer {
+    pub fn new() -> Self {
+        Self {
+            labels: Dictionary::default(),
+            attrs: Dictionary::default(),
+            instances: Vec::new(),
+            prune_threshold: 0.0,
+        }
+    }
+
+    pub fn set_prune_threshold(&mut self, threshold: f64) {
+        self.prune_threshold = threshold;
+    }
+
+    pub fn append<X, Y>(&mut self, xseq: &[X], yseq: &[Y]) -> Result<()>
+    where
+        X: AsRef<[Attribute]>,
+        Y: AsRef<str>,
+    {
+        if xseq.len() != yseq.len() {
+            bail!("the number of items and labels differ");
+        }
+        let items = xseq
+            .iter()
+            .map(|item| {
+                item.as_ref()
+                    .iter()
+                    .map(|attr| (self.attrs.insert(&attr.name), attr.value))
+                    .collect()
+            })
+            .collect();
+        let labels = yseq
+            .iter()
+            .map(|label| self.labels.insert(label.as_ref()))
+            .collect();
+        self.instances.push(Instance { items, labels });
+        Ok(())
+    }
+
+    pub fn train(&self) -> Result<Vec<u8>> {
+        let mut encoder = Encoder::new(self);
+        let mut weights = vec![0.0; encoder.features.len()];
+        lbfgs::minimize(&mut weights, |w, g| {
+            let mut f = encoder.objective_and_gradients(&self.instances, w, g);
+            for (g, w) in g.iter_mut().zip(w) {
+                *g += 2.0 * C2 * w;
+                f += C2 * w * w;
+            }
+            f
+        });
+        Ok(encoder.save_model(self, &weights)?)
+    }
+}
+
+impl Default for Trainer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Default)]
+struct Dictionary {
+    ids: HashMap<String, u32>,
+    names: Vec<String>,
+}
+
+impl Dictionary {
+    fn insert(&mut self, name: &str) -> u32 {
+        if let Some(id) = self.ids.get(name) {
+            return *id;
+        }
+        let id = self.names.len() as u32;
+        self.ids.insert(name.to_string(), id);
+        self.names.push(name.to_string());
+        id
+    }
+
+    fn len(&self) -> usize {
+        self.names.len()
+    }
+}
+
+struct Instance {
+    items: Vec<Vec<(u32, f64)>>,
+    labels: Vec<u32>,
+}
+
+struct Feature {
+    kind: u32,
+    src: u32,
+    dst: u32,
+    freq: f64,
+}
+
+struct Encoder {
+    num_labels: usize,
+    features: Vec<Feature>,
+    attr_refs: Vec<Vec<usize>>,
+    trans_refs: Vec<Vec<usize>>,
+    context: Context,
+}
+
+impl Encoder {
+    fn new(trainer: &Trainer) -> Self {
+        let num_labels = trainer.labels.len();
+        let mut set = BTreeMap::<(u32, u32, u32), f64>::new();
+        for inst in &trainer.instances {
+            let mut prev = None;
+            for (item, &cur) in inst.items.iter().zip(&inst.labels) {
+                if let Some(prev) = prev {
+                    *set.entry((TRANSITION_FEATURE, prev, cur)).or_default() += 1.0;
+                }
+                for &(aid, value) in item {
+                    *set.entry((STATE_FEATURE, aid, cur)).or_default() += value;
+                }
+                prev = Some(cur);
+            }
+        }
+
+        let features = set
+            .into_iter()
+            .map(|((kind, src, dst), freq)| Feature {
+                kind,
+                src,
+                dst,
+                freq,
+            })
+            .collect::<Vec<_>>();
+
+        let mut attr_refs = vec![Vec::new(); trainer.attrs.len()];
+        let mut trans_refs = vec![Vec::new(); num_labels];
+        for (fid, feature) in features.iter().enumerate() {
+            if feature.kind == STATE_FEATURE {
+                attr_refs[feature.src as usize].push(fid);
+            } else {
+                trans_refs[feature.src as usize].push(fid);
+            }
+        }
+
+        let max_items = trainer
+            .instances
+            .iter()
+            .map(|inst| inst.items.len())
+            .max()
+            .unwrap_or(0);
+
+        Self {
+            num_labels,
+            features,
+            attr_refs,
+            trans_refs,
+            context: Context::new(num_labels, max_items),
+        }
+    }
+
+    fn objective_and_gradients(&mut self, instances: &[Instance], w: &[f64], g: &mut [f64]) -> f64 {
+        let l = self.num_labels;
+        for (g, feature) in g.iter_mut().zip(&self.features) {
+            *g = -feature.freq;
+        }
+
+        let ctx = &mut self.context;
+        ctx.trans.iter_mut().for_each(|v| *v = 0.0);
+        for (i, refs) in self.trans_refs.iter().enumerate() {
+            for &fid in refs {
+                ctx.trans[i * l + self.features[fid].dst as usize] = w[fid];
+            }
+        }
+        for (exp, v) in ctx.exp_trans.iter_mut().zip(&ctx.trans) {
+            *exp = v.exp();
+        }
+
+        let mut logl = 0.0;
+        for inst in instances {
+            let t_len = inst.items.len();
+            if t_len == 0 {
+                continue;
+            }
+            ctx.reset(t_len);
+
+            for (t, item) in inst.items.iter().enumerate() {
+                let state = &mut ctx.state[t * l..(t + 1) * l];
+                for &(aid, value) in item {
+                    for &fid in &self.attr_refs[aid as usize] {
+                        state[self.features[fid].dst as usize] += w[fid] * value;
+                    }
+                }
+            }
+            for (exp, v) in ctx.exp_state[..t_len * l]
+                .iter_mut()
+                .zip(&ctx.state[..t_len * l])
+            {
+                *exp = v.exp();
+            }
+
+            ctx.alpha_score(t_len);
+            ctx.beta_score(t_len);
+            ctx.marginals(t_len);
+
+            logl += ctx.score(&inst.labels) - ctx.log_norm;
+
+            for (t, item) in inst.items.iter().enumerate() {
+                let prob = &ctx.mexp_state[t * l..(t + 1) * l];
+                for &(aid, value) in item {
+                    for &fid in &self.attr_refs[aid as usize] {
+                        g[fid] += prob[self.features[fid].dst as usize] * value;
+                    }
+                }
+            }
+            for (i, refs) in self.trans_refs.iter().enumerate() {
+                for &fid in refs {
+                    g[fid] += ctx.mexp_trans[i * l + self.features[fid].dst as usize];
+                }
+            }
+        }
+
+        -logl
+    }
+
+    fn save_model(&self, trainer: &Trainer, w: &[f64]) -> std::io::Result<Vec<u8>> {
+        let mut fmap = vec![None; self.features.len()];
+        let mut amap = vec![None; trainer.attrs.len()];
+        let mut num_features: u32 = 0;
+        let mut num_attrs: u32 = 0;
+
+        let mut buf = Cursor::new(Vec::new());
+        buf.seek(SeekFrom::Start(HEADER_SIZE))?;
+
+        let off_features = buf.position();
+        buf.seek(SeekFrom::Current(CHUNK_SIZE as i64))?;
+        for (k, feature) in self.features.iter().enumerate() {
+            if w[k] == 0.0 || w[k].abs() < trainer.prune_threshold {
+                continue;
+            }
+            fmap[k] = Some(num_features);
+            num_features += 1;
+            let src = if feature.kind == STATE_FEATURE {
+                *amap[feature.src as usize].get_or_insert_with(|| {
+                    num_attrs += 1;
+                    num_attrs - 1
+                })
+            } else {
+                feature.src
+            };
+            buf.write_all(&feature.kind.to_le_bytes())?;
+            buf.write_all(&src.to_le_bytes())?;
+            buf.write_all(&feature.dst.to_le_bytes())?;
+            buf.write_all(&w[k].to_le_bytes())?;
+        }
+        let end = buf.position();
+        buf.seek(SeekFrom::Start(off_features))?;
+        buf.write_all(b"FEAT")?;
+        buf.write_all(&((end - off_features) as u32).to_le_bytes())?;
+        buf.write_all(&num_features.to_le_bytes())?;
+        buf.seek(SeekFrom::Start(end))?;
+
+        let off_labels = buf.position();
+        {
+            let mut writer = CQDBWriter::new(&mut buf)?;
+            for (lid, label) in trainer.labels.names.iter().enumerate() {
+                writer.put(label, lid as u32)?;
+            }
+        }
+
+        let off_attrs = buf.position();
+        {
+            let mut writer = CQDBWriter::new(&mut buf)?;
+            for (aid, attr) in trainer.attrs.names.iter().enumerate() {
+                if let Some(id) = amap[aid] {
+                    writer.put(attr, id)?;
+                }
+            }
+        }
+
+        let mut label_refs = vec![None; self.num_labels + 2];
+        for (lid, refs) in self.trans_refs.iter().enumerate() {
+            label_refs[lid] = Some(refs.as_slice());
+        }
+        let off_label_refs = write_refs(&mut buf, b"LFRF", &label_refs, &fmap)?;
+
+        let mut attr_refs = vec![None; num_attrs as usize];
+        for (aid, refs) in self.attr_refs.iter().enumerate() {
+            if let Some(id) = amap[aid] {
+                attr_refs[id as usize] = Some(refs.as_slice());
+            }
+        }
+        let off_attr_refs = write_refs(&mut buf, b"AFRF", &attr_refs, &fmap)?;
+
+        let size = buf.position();
+        buf.seek(SeekFrom::Start(0))?;
+        buf.write_all(b"lCRF")?;
+        buf.write_all(&(size as u32).to_le_bytes())?;
+        buf.write_all(b"FOMC")?;
+        for value in [
+            100,
+            num_features,
+            self.num_labels as u32,
+            num_attrs,
+            off_features as u32,
+            off_labels as u32,
+            off_attrs as u32,
+            off_label_refs as u32,
+            off_attr_refs as u32,
+        ] {
+            buf.write_all(&value.to_le_bytes())?;
+        }
+
+        Ok(buf.into_inner())
+    }
+}
+
+fn write_refs(
+    buf: &mut Cursor<Vec<u8>>,
+    chunk: &[u8; 4],
+    refs: &[Option<&[usize]>],
+    fmap: &[Option<u32>],
+) -> std::io::Result<u64> {
+    while !buf.position().is_multiple_of(4) {
+        buf.write_all(&[0])?;
+    }
+
+    let begin = buf.position();
+    buf.seek(SeekFrom::Current(
+        (CHUNK_SIZE + 4 * refs.len() as u64) as i64,
+    ))?;
+
+    let mut offsets = vec![0u32; refs.len()];
+    for (offset, fids) in offsets.iter_mut().zip(refs) {
+        if let Some(fids) = fids {
+            *offset = buf.position() as u32;
+            let fids = fids.iter().filter_map(|fid| fmap[*fid]).collect::<Vec<_>>();
+            buf.write_all(&(fids.len() as u32).to_le_bytes())?;
+            for fid in fids {
+                buf.write_all(&fid.to_le_bytes())?;
+            }
+        }
+    }
+
+    let end = buf.position();
+    buf.seek(SeekFrom::Start(begin))?;
+    buf.write_all(chunk)?;
+    buf.write_all(&((end - begin) as u32).to_le_bytes())?;
+    buf.write_all(&(refs.len() as u32).to_le_bytes())?;
+    for offset in offsets {
+        buf.write_all(&offset.to_le_bytes())?;
+    }
+    buf.seek(SeekFrom::Start(end))?;
+    Ok(begin)
+}
+
+struct Context {
+    num_labels: usize,
+    state: Vec<f64>,
+    trans: Vec<f64>,
+    exp_state: Vec<f64>,
+    exp_trans: Vec<f64>,
+    alpha: Vec<f64>,
+    beta: Vec<f64>,
+    scale: Vec<f64>,
+    row: Vec<f64>,
+    mexp_state: Vec<f64>,
+    mexp_trans: Vec<f64>,
+    log_norm: f64,
+}
+
+impl Context {
+    fn new(num_labels: usize, max_items: usize) -> Self {
+        let l = num_labels;
+        let t = max_items;
+        Self {
+            num_labels,
+            state: vec![0.0; t * l],
+            trans: vec![0.0; l * l],
+            exp_state: vec![0.0; t * l],
+            exp_trans: vec![0.0; l * l],
+            alpha: vec![0.0; t * l],
+            beta: vec![0.0; t * l],
+            scale: vec![0.0; t],
+            row: vec![0.0; l],
+            mexp_state: vec![0.0; t * l],
+            mexp_trans: vec![0.0; l * l],
+            log_norm: 0.0,
+        }
+    }
+
+    fn reset(&mut self, t_len: usize) {
+        let n = t_len * self.num_labels;
+        self.state[..n].iter_mut().for_each(|v| *v = 0.0);
+        self.mexp_state[..n].iter_mut().for_each(|v| *v = 0.0);
+        self.mexp_trans.iter_mut().for_each(|v| *v = 0.0);
+        self.log_norm = 0.0;
+    }
+
+    fn alpha_score(&mut self, t_len: usize) {
+        let l = self.num_labels;
+
+        self.alpha[..l].copy_from_slice(&self.exp_state[..l]);
+        self.scale[0] = normalize(&mut self.alpha[..l]);
+
+        for t in 1..t_len {
+            let (prev, cur) = self.alpha.split_at_mut(t * l);
+            let prev = &prev[(t - 1) * l..];
+            let cur = &mut cur[..l];
+            cur.iter_mut().for_each(|v| *v = 0.0);
+            for (i, &p) in prev.iter().enumerate() {
+                let trans = &self.exp_trans[i * l..(i + 1) * l];
+                for (c, tr) in cur.iter_mut().zip(trans) {
+                    *c += p * tr;
+                }
+            }
+            for (c, s) in cur.iter_mut().zip(&self.exp_state[t * l..(t + 1) * l]) {
+                *c *= s;
+            }
+            self.scale[t] = normalize(cur);
+        }
+
+        self.log_norm = -self.scale[..t_len].iter().map(|s| s.ln()).sum::<f64>();
+    }
+
+    fn beta_score(&mut self, t_len: usize) {
+        let l = self.num_labels;
+
+        let last = (t_len - 1) * l;
+        let scale = self.scale[t_len - 1];
+        self.beta[last..last + l]
+            .iter_mut()
+            .for_each(|v| *v = scale);
+
+        for t in (0..t_len - 1).rev() {
+            let (cur, next) = self.beta.split_at_mut((t + 1) * l);
+            let cur = &mut cur[t * l..];
+            let state = &self.exp_state[(t + 1) * l..(t + 2) * l];
+            for ((r, n), s) in self.row.iter_mut().zip(&next[..l]).zip(state) {
+                *r = n * s;
+            }
+            for (i, c) in cur.iter_mut().enumerate() {
+                let trans = &self.exp_trans[i * l..(i + 1) * l];
+                *c = trans.iter().zip(&self.row).map(|(a, b)| a * b).sum::<f64>() * self.scale[t];
+            }
+        }
+    }
+
+    fn marginals(&mut self, t_len: usize) {
+        let l = self.num_labels;
+
+        for t in 0..t_len {
+            let range = t * l..(t + 1) * l;
+            let scale = 1.0 / self.scale[t];
+            for ((p, a), b) in self.mexp_state[range.clone()]
+                .iter_mut()
+                .zip(&self.alpha[range.clone()])
+                .zip(&self.beta[range])
+            {
+                *p = a * b * scale;
+            }
+        }
+
+        for t in 0..t_len - 1 {
+            let fwd = &self.alpha[t * l..(t + 1) * l];
+            let state = &self.exp_state[(t + 1) * l..(t + 2) * l];
+            let bwd = &self.beta[(t + 1) * l..(t + 2) * l];
+            for ((r, b), s) in self.row.iter_mut().zip(bwd).zip(state) {
+                *r = b * s;
+            }
+            for (i, f) in fwd.iter().enumerate() {
+                let edge = &self.exp_trans[i * l..(i + 1) * l];
+                let prob = &mut self.mexp_trans[i * l..(i + 1) * l];
+                for ((p, e), r) in prob.iter_mut().zip(edge).zip(&self.row) {
+                    *p += f * e * r;
+                }
+            }
+        }
+    }
+
+    fn score(&self, labels: &[u32]) -> f64 {
+        let l = self.num_labels;
+        let mut prev = labels[0] as usize;
+        let mut ret = self.state[prev];
+        for (t, &label) in labels.iter().enumerate().skip(1) {
+            let cur = label as usize;
+            ret += self.trans[prev * l + cur] + self.state[t * l + cur];
+            prev = cur;
+        }
+        ret
+    }
+}
+
+fn normalize(values: &mut [f64]) -> f64 {
+    let sum = values.iter().sum::<f64>();
+    let scale = if sum != 0.0 { 1.0 / sum } else { 1.0 };
+    values.iter_mut().for_each(|v| *v *= scale);
+    scale
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crfs::Model;
+
+    fn attrs(names: &[(&str, f64)]) -> Vec<Attribute> {
+        names
+            .iter()
+            .map(|(name, value)| Attribute::new(*name, *value))
+            .collect()
+    }
+
+    #[test]
+    fn test_train() {
+        let mut trainer = Trainer::new();
+        let xseq = [
+            attrs(&[("walk", 1.0), ("shop", 0.5)]),
+            attrs(&[("walk", 1.0)]),
+            attrs(&[("walk", 1.0), ("clean", 0.5)]),
+            attrs(&[("shop", 0.5), ("clean", 0.5)]),
+            attrs(&[("walk", 0.5), ("clean", 1.0)]),
+            attrs(&[("clean", 1.0), ("shop", 0.1)]),
+            attrs(&[("walk", 1.0), ("shop", 0.5)]),
+            attrs(&[]),
+            attrs(&[("clean", 1.0)]),
+        ];
+        let yseq = [
+            "sunny", "sunny", "sunny", "rainy", "rainy", "rainy", "sunny", "sunny", "rainy",
+        ];
+        trainer.append(&xseq, &yseq).unwrap();
+        assert!(trainer.append(&xseq, &yseq[1..]).is_err());
+
+        let data = trainer.train().unwrap();
+        let model = Model::new(&data).unwrap();
+        assert_eq!(model.num_labels(), 2);
+        assert_eq!(model.num_attrs(), 3);
+
+        let mut tagger = model.tagger().unwrap();
+        assert_eq!(tagger.tag(&xseq).unwrap(), yseq);
+    }
+}
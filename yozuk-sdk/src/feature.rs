@@ -13,3 +13,18 @@ impl fmt::Display for Feature {
         write!(f, "{}@{}", self.name, self.pos)
     }
 }
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct FeatureSet {
+    pub char_ngrams: bool,
+    pub word_shape: bool,
+    pub position: bool,
+}
+
+impl FeatureSet {
+    pub const ALL: Self = Self {
+        char_ngrams: true,
+        word_shape: true,
+        position: true,
+    };
+}
@@ -1,61 +1,203 @@
-use anyhow::Result;
+use crate::feature::FeatureSet;
+use anyhow::{bail, Result};
 use bytes::Bytes;
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use serde_derive::{Deserialize, Serialize};
 use std::{
-    io::{Cursor, Write},
+    io::{Cursor, Read, Write},
     ops::Range,
+    sync::OnceLock,
 };
 
 pub struct ModelSet {
     data: Bytes,
-    keys: Vec<(String, Range<usize>)>,
+    keys: Vec<Section>,
     header_len: usize,
+    compressed: bool,
+    sections: Vec<OnceLock<Option<Bytes>>>,
 }
 
 impl ModelSet {
     pub fn new<D, I>(data: D, keys: I) -> Self
     where
         D: Into<Bytes>,
-        I: IntoIterator<Item = (String, Range<usize>)>,
+        I: IntoIterator<Item = (String, FeatureSet, Range<usize>)>,
     {
-        Self {
-            data: data.into(),
-            keys: keys.into_iter().collect(),
-            header_len: 0,
-        }
+        let keys = keys
+            .into_iter()
+            .map(|(key, features, range)| Section {
+                key,
+                features,
+                range,
+            })
+            .collect();
+        Self::with_sections(data.into(), keys, 0, false)
     }
 
     pub fn from_data<T: Into<Bytes>>(data: T) -> Result<Self> {
         let data = data.into();
 
         let mut cursor = Cursor::new(&data);
-        let keys = bincode::deserialize_from(&mut cursor)?;
-        let header_len = cursor.position() as _;
-        Ok(Self {
+        let (compressed, keys): (bool, Vec<Section>) = bincode::deserialize_from(&mut cursor)?;
+        let header_len = cursor.position() as usize;
+        if keys
+            .iter()
+            .any(|section| section.range.end > data.len() - header_len)
+        {
+            bail!("model data is truncated");
+        }
+        Ok(Self::with_sections(data, keys, header_len, compressed))
+    }
+
+    fn with_sections(data: Bytes, keys: Vec<Section>, header_len: usize, compressed: bool) -> Self {
+        let sections = keys.iter().map(|_| OnceLock::new()).collect();
+        Self {
             data,
             keys,
             header_len,
-        })
+            compressed,
+            sections,
+        }
+    }
+
+    pub fn compress(&self) -> Result<Self> {
+        if self.compressed {
+            return Ok(Self::with_sections(
+                self.data.clone(),
+                self.keys.clone(),
+                self.header_len,
+                true,
+            ));
+        }
+
+        let mut data = Vec::new();
+        let mut keys = Vec::new();
+        for section in &self.keys {
+            let start = data.len();
+            if !section.range.is_empty() {
+                let mut encoder = DeflateEncoder::new(&mut data, Compression::best());
+                encoder.write_all(&self.raw_section(&section.range))?;
+                encoder.finish()?;
+            }
+            keys.push(Section {
+                range: start..data.len(),
+                ..section.clone()
+            });
+        }
+        Ok(Self::with_sections(data.into(), keys, 0, true))
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        self.compressed
     }
 
     pub fn get(&self, key: &str) -> Option<Bytes> {
-        self.get_index(key)
-            .map(|index| self.keys[index].1.clone())
-            .filter(|range| !range.is_empty())
-            .map(|range| {
-                self.data
-                    .slice(range.start + self.header_len..range.end + self.header_len)
+        let index = self.get_index(key)?;
+        let range = &self.keys[index].range;
+        if range.is_empty() {
+            return None;
+        }
+        if !self.compressed {
+            return Some(self.raw_section(range));
+        }
+        self.sections[index]
+            .get_or_init(|| {
+                let mut data = Vec::new();
+                DeflateDecoder::new(&self.raw_section(range)[..])
+                    .read_to_end(&mut data)
+                    .ok()
+                    .map(|_| data.into())
             })
+            .clone()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        for section in &self.keys {
+            if !section.range.is_empty() && self.get(&section.key).is_none() {
+                bail!("model section {} is corrupted", section.key);
+            }
+        }
+        Ok(())
     }
 
     pub fn get_index(&self, key: &str) -> Option<usize> {
         self.keys
-            .binary_search_by(|entry| entry.0.as_str().cmp(key))
+            .binary_search_by(|section| section.key.as_str().cmp(key))
             .ok()
     }
 
+    pub fn features(&self, key: &str) -> Option<FeatureSet> {
+        self.get_index(key).map(|index| self.keys[index].features)
+    }
+
+    pub fn stored_sizes(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.keys
+            .iter()
+            .map(|section| (section.key.as_str(), section.range.len()))
+    }
+
     pub fn write<W: Write>(&self, mut dst: W) -> bincode::Result<()> {
-        bincode::serialize_into(&mut dst, &self.keys)?;
-        dst.write_all(&self.data)?;
+        bincode::serialize_into(&mut dst, &(self.compressed, &self.keys))?;
+        dst.write_all(&self.data[self.header_len..])?;
         Ok(())
     }
+
+    fn raw_section(&self, range: &Range<usize>) -> Bytes {
+        self.data
+            .slice(range.start + self.header_len..range.end + self.header_len)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Section {
+    key: String,
+    features: FeatureSet,
+    range: Range<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_section_features() {
+        let features = FeatureSet {
+            word_shape: true,
+            ..Default::default()
+        };
+        let model = ModelSet::new(
+            b"abcdef".to_vec(),
+            [
+                ("a".to_string(), FeatureSet::default(), 0..3),
+                ("b".to_string(), features, 3..6),
+            ],
+        );
+
+        let mut data = Vec::new();
+        model.compress().unwrap().write(&mut data).unwrap();
+        let model = ModelSet::from_data(data).unwrap();
+        assert_eq!(model.features("a"), Some(FeatureSet::default()));
+        assert_eq!(model.features("b"), Some(features));
+        assert_eq!(model.features("c"), None);
+        assert_eq!(model.get("b").as_deref(), Some(&b"def"[..]));
+    }
+
+    #[test]
+    fn test_validate() {
+        let model = ModelSet::new(
+            b"abcdef".to_vec(),
+            [("a".to_string(), FeatureSet::default(), 0..6)],
+        );
+        assert!(model.validate().is_ok());
+        assert!(model.compress().unwrap().validate().is_ok());
+
+        let corrupted = ModelSet {
+            compressed: true,
+            ..ModelSet::new(
+                b"abcdef".to_vec(),
+                [("a".to_string(), FeatureSet::default(), 0..6)],
+            )
+        };
+        assert!(corrupted.validate().is_err());
+    }
 }
@@ -22,12 +22,29 @@ pub trait Corpus: Send + Sync + 'static {
     fn weight(&self) -> f64 {
         1.0
     }
+    fn augmentation(&self) -> Augmentation {
+        Augmentation::default()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Augmentation {
+    pub typos: f64,
+    pub casing: f64,
+    pub politeness: f64,
+    pub questions: f64,
+    pub synonyms: f64,
+    pub synonym_groups: Vec<Vec<String>>,
 }
 
 pub trait Suggestions: Send + Sync + 'static {
     fn suggestions(&self, seed: u64, args: &[Token], streams: &[InputStream]) -> Vec<String>;
 }
 
+pub trait Completions: Send + Sync + 'static {
+    fn completions(&self) -> Vec<String>;
+}
+
 pub trait Preprocessor: Send + Sync + 'static {
     fn preprocess(&self, input: Vec<Token>) -> Vec<Token>;
 }
@@ -50,12 +67,15 @@ pub trait Command: Send + Sync + 'static {
 
 #[derive(Default)]
 pub struct Skill {
+    pub title: String,
     pub corpora: Vec<Box<dyn Corpus>>,
     pub suggestions: Vec<Box<dyn Suggestions>>,
+    pub completions: Vec<Box<dyn Completions>>,
     pub labelers: Vec<Box<dyn Labeler>>,
     pub preprocessors: Vec<Box<dyn Preprocessor>>,
     pub translators: Vec<Box<dyn Translator>>,
     pub command: Option<Box<dyn Command>>,
+    pub features: FeatureSet,
 }
 
 impl Skill {
@@ -70,6 +90,11 @@ pub struct SkillBuilder {
 }
 
 impl SkillBuilder {
+    pub fn set_title<T: Into<String>>(mut self, title: T) -> Self {
+        self.skill.title = title.into();
+        self
+    }
+
     pub fn add_corpus<T: Corpus>(mut self, item: T) -> Self {
         self.skill.corpora.push(Box::new(item));
         self
@@ -80,6 +105,11 @@ impl SkillBuilder {
         self
     }
 
+    pub fn add_completions<T: Completions>(mut self, item: T) -> Self {
+        self.skill.completions.push(Box::new(item));
+        self
+    }
+
     pub fn add_labeler<T: Labeler>(mut self, item: T) -> Self {
         self.skill.labelers.push(Box::new(item));
         self
@@ -100,6 +130,11 @@ impl SkillBuilder {
         self
     }
 
+    pub fn set_features(mut self, features: FeatureSet) -> Self {
+        self.skill.features = features;
+        self
+    }
+
     pub fn build(self) -> Result<Skill> {
         Ok(self.skill)
     }
@@ -8,6 +8,7 @@ pub struct InputStream {
     header: Option<Box<[u8]>>,
     offset: usize,
     media_type: MediaTypeBuf,
+    file_name: Option<String>,
 }
 
 impl InputStream {
@@ -21,9 +22,15 @@ impl InputStream {
             header: None,
             offset: 0,
             media_type: media_type.into(),
+            file_name: None,
         }
     }
 
+    pub fn set_file_name<T: Into<String>>(mut self, file_name: T) -> Self {
+        self.file_name = Some(file_name.into());
+        self
+    }
+
     pub fn read_header(&mut self) -> Result<&[u8]> {
         if self.header.is_none() {
             let mut header = vec![0; HEADER_LENGTH];
@@ -45,6 +52,10 @@ impl InputStream {
     pub fn media_type(&self) -> &MediaTypeBuf {
         &self.media_type
     }
+
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
 }
 
 impl Read for InputStream {
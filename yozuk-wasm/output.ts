@@ -1,83 +1,19 @@
-export type CommentBlock = {
-    type: "comment";
-    title?: string;
-    text: string;
-    media_type: string;
-};
-
-export type DataBlock = {
-    type: "data";
-    data: string | ArrayBuffer;
-    title?: string;
-    file_name?: string;
-    media_type: string;
-    display?: DisplaySuggestion;
-    highlights?: Highlight[];
-};
-
-export type Block =
-    | CommentBlock
-    | DataBlock;
-
-export type LinkMetadata = {
-    type: "link";
-    title: string;
-    url: string;
-};
-
-export type DocsMetadata = {
-    type: "docs";
-    url: string;
-};
-
-export type ValueMetadata = {
-    type: "value";
-    value: any;
-};
-
-export type ColorMetadata = {
-    type: "color";
-    color: string;
-};
-
-export type Metadata =
-    | LinkMetadata
-    | DocsMetadata
-    | ValueMetadata
-    | ColorMetadata;
-
-export type DisplaySuggestion = {
-    binary?: "viewer" | "base64" | "hex";
-    image?: "smooth" | "pixelated";
-};
-
-export type Highlight = {
-    kind: "value";
-    range: { start: number, end: number };
-};
-
-export type Output = {
-    title: string;
-    blocks: Block[];
-    metadata: Metadata[];
-    mode: "primary" | "attachment";
-};
-
-export type ResultOk = {
-    type: "ok";
-    outputs: Output[];
-};
-
-export type ResultFail = {
-    type: "fail";
-    outputs: Output[];
-};
-
-export type ResultNoCoammnd = {
-    type: "no_command";
-};
-
-export type Result =
-    | ResultOk
-    | ResultFail
-    | ResultNoCoammnd;
\ No newline at end of file
+export type {
+    CommentBlock,
+    DataBlock,
+    Block,
+    LinkMetadata,
+    DocsMetadata,
+    ValueMetadata,
+    ColorMetadata,
+    Metadata,
+    DisplaySuggestion,
+    Highlight,
+    Output,
+    ResultOk,
+    ResultFail,
+    ResultNoCommand,
+    Result,
+    CommandArgs,
+    UserContext,
+} from './wasm-web/yozuk_wasm';
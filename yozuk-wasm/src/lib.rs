@@ -1,107 +1,312 @@
 #![cfg(all(target_arch = "wasm32", target_os = "unknown"))]
 
-use mediatype::media_type;
+use js_sys::{Reflect, Uint8Array};
 use once_cell::sync::OnceCell;
-use serde_derive::{Deserialize, Serialize};
+use serde_derive::Serialize;
+use serde_wasm_bindgen::Serializer;
 use std::io::Cursor;
-use std::mem;
-use std::ops::DerefMut;
-use std::sync::Mutex;
+use std::str;
 use wasm_bindgen::prelude::*;
-use yozuk::Yozuk;
+use wasm_bindgen::JsCast;
 use yozuk_sdk::prelude::*;
 
-fn global_streams() -> &'static Mutex<Vec<Box<[u8]>>> {
-    static INSTANCE: OnceCell<Mutex<Vec<Box<[u8]>>>> = OnceCell::new();
-    INSTANCE.get_or_init(|| Mutex::new(Vec::new()))
-}
+#[wasm_bindgen(typescript_custom_section)]
+const TYPES: &'static str = r#"
+export type UserContext = {
+    username?: string;
+    locale?: string;
+    timezone?: string;
+};
 
-fn global_suggestions_streams() -> &'static Mutex<Vec<Box<[u8]>>> {
-    static INSTANCE: OnceCell<Mutex<Vec<Box<[u8]>>>> = OnceCell::new();
-    INSTANCE.get_or_init(|| Mutex::new(Vec::new()))
-}
+export type CommandArgs = {
+    args: string[];
+    data: string[];
+};
 
-fn global_yozuk() -> &'static Yozuk {
-    static INSTANCE: OnceCell<Yozuk> = OnceCell::new();
-    INSTANCE.get_or_init(|| Yozuk::builder().build())
-}
+export type CommentBlock = {
+    type: "comment";
+    title?: string;
+    text: string;
+    media_type: string;
+};
+
+export type DataBlock = {
+    type: "data";
+    data: string | Uint8Array;
+    blob?: Blob;
+    title?: string;
+    file_name?: string;
+    media_type: string;
+    display?: DisplaySuggestion;
+    highlights?: Highlight[];
+};
+
+export type Block =
+    | CommentBlock
+    | DataBlock;
+
+export type LinkMetadata = {
+    type: "link";
+    title: string;
+    url: string;
+};
+
+export type DocsMetadata = {
+    type: "docs";
+    url: string;
+};
+
+export type ValueMetadata = {
+    type: "value";
+    value: any;
+};
+
+export type ColorMetadata = {
+    type: "color";
+    color: string;
+};
+
+export type Metadata =
+    | LinkMetadata
+    | DocsMetadata
+    | ValueMetadata
+    | ColorMetadata;
+
+export type DisplaySuggestion = {
+    binary?: "viewer" | "base64" | "hex";
+    image?: "smooth" | "pixelated";
+};
+
+export type Highlight = {
+    kind: "value";
+    range: { start: number, end: number };
+};
+
+export type Output = {
+    title: string;
+    blocks: Block[];
+    metadata: Metadata[];
+    mode: "primary" | "attachment";
+};
+
+export type ResultOk = {
+    type: "ok";
+    outputs: Output[];
+};
+
+export type ResultFail = {
+    type: "fail";
+    outputs: Output[];
+};
+
+export type ResultNoCommand = {
+    type: "no_command";
+};
+
+export type Result =
+    | ResultOk
+    | ResultFail
+    | ResultNoCommand;
+"#;
 
 #[wasm_bindgen]
-pub fn push_stream(buffer: Box<[u8]>) {
-    global_streams().lock().unwrap().push(buffer);
+extern "C" {
+    #[wasm_bindgen(typescript_type = "UserContext")]
+    pub type JsUserContext;
+
+    #[wasm_bindgen(typescript_type = "CommandArgs[]")]
+    pub type JsCommandArgsList;
+
+    #[wasm_bindgen(typescript_type = "Result")]
+    pub type JsResult;
+
+    #[wasm_bindgen(typescript_type = "string[]")]
+    pub type JsStringList;
 }
 
-#[wasm_bindgen]
-pub fn random_suggestions(amount: usize) -> String {
-    serde_json::to_string(&global_yozuk().random_suggestions(amount)).unwrap()
+fn global_yozuk() -> &'static yozuk::Yozuk {
+    static INSTANCE: OnceCell<yozuk::Yozuk> = OnceCell::new();
+    INSTANCE.get_or_init(|| yozuk::Yozuk::builder().build())
 }
 
-#[wasm_bindgen]
-pub fn push_suggestions_stream(buffer: Box<[u8]>) {
-    global_suggestions_streams().lock().unwrap().push(buffer);
+struct StreamData {
+    data: Box<[u8]>,
+    media_type: MediaTypeBuf,
 }
 
 #[wasm_bindgen]
-pub fn clear_suggestions_stream() {
-    global_suggestions_streams().lock().unwrap().clear();
+#[derive(Default)]
+pub struct Yozuk {
+    streams: Vec<StreamData>,
 }
 
 #[wasm_bindgen]
-pub fn suggestions(command: &str, amount: usize) -> String {
-    let tokens = Tokenizer::new().tokenize(command);
-    let streams = global_suggestions_streams()
-        .lock()
-        .unwrap()
-        .iter()
-        .map(|data| {
-            InputStream::new(
-                Cursor::new(data.clone()),
-                media_type!(APPLICATION / OCTET_STREAM),
-            )
-        })
-        .collect::<Vec<_>>();
-    serde_json::to_string(&global_yozuk().suggestions(&tokens, &streams, amount)).unwrap()
+impl Yozuk {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    #[wasm_bindgen(js_name = pushStream)]
+    pub fn push_stream(
+        &mut self,
+        data: Box<[u8]>,
+        media_type: Option<String>,
+    ) -> Result<(), JsValue> {
+        let media_type = match media_type.filter(|ty| !ty.is_empty()) {
+            Some(ty) => MediaTypeBuf::from_string(ty)
+                .map_err(|err| JsValue::from_str(&format!("invalid media type: {}", err)))?,
+            None => media_type!(APPLICATION / OCTET_STREAM).into(),
+        };
+        self.streams.push(StreamData { data, media_type });
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = clearStreams)]
+    pub fn clear_streams(&mut self) {
+        self.streams.clear();
+    }
+
+    #[wasm_bindgen(js_name = getCommands)]
+    pub fn get_commands(&self, input: &str) -> Result<JsCommandArgsList, JsValue> {
+        let tokens = Tokenizer::new().tokenize(input);
+        let commands = global_yozuk().get_commands(&tokens, &self.input_streams()?);
+        Ok(to_value(&commands)?.unchecked_into())
+    }
+
+    #[wasm_bindgen(js_name = runCommands)]
+    pub fn run_commands(
+        &self,
+        commands: JsCommandArgsList,
+        user: Option<JsUserContext>,
+    ) -> Result<JsResult, JsValue> {
+        let commands: Vec<CommandArgs> = serde_wasm_bindgen::from_value(commands.into())?;
+        self.run(commands, user)
+    }
+
+    pub fn exec(&self, input: &str, user: Option<JsUserContext>) -> Result<JsResult, JsValue> {
+        let tokens = Tokenizer::new().tokenize(input);
+        let commands = global_yozuk().get_commands(&tokens, &self.input_streams()?);
+        self.run(commands, user)
+    }
+
+    pub fn suggest(&self, input: &str, amount: Option<usize>) -> Result<JsStringList, JsValue> {
+        let amount = amount.unwrap_or(5);
+        let suggestions = if input.trim().is_empty() && self.streams.is_empty() {
+            global_yozuk().random_suggestions(amount)
+        } else {
+            let tokens = Tokenizer::new().tokenize(input);
+            global_yozuk().suggestions(&tokens, &self.input_streams()?, amount)
+        };
+        Ok(to_value(&suggestions)?.unchecked_into())
+    }
 }
 
-#[wasm_bindgen]
-pub fn exec(command: &str, user: &str) -> Result<String, JsValue> {
-    let streams = mem::take(global_streams().lock().unwrap().deref_mut());
-    let input = JsonInput {
-        tokens: Tokenizer::new().tokenize(command),
-        user: serde_json::from_str(user).unwrap_or_default(),
-    };
-    let result = run(input, streams);
-    Ok(serde_json::to_string(&result).unwrap())
+impl Yozuk {
+    fn input_streams(&self) -> Result<Vec<InputStream>, JsValue> {
+        self.streams
+            .iter()
+            .map(|stream| {
+                let mut stream =
+                    InputStream::new(Cursor::new(stream.data.clone()), stream.media_type.clone());
+                stream
+                    .read_header()
+                    .map_err(|err| JsValue::from_str(&err.to_string()))?;
+                Ok(stream)
+            })
+            .collect()
+    }
+
+    fn run(
+        &self,
+        commands: Vec<CommandArgs>,
+        user: Option<JsUserContext>,
+    ) -> Result<JsResult, JsValue> {
+        let user = match user {
+            Some(user) => serde_wasm_bindgen::from_value(user.into())?,
+            None => UserContext::default(),
+        };
+        if commands.is_empty() {
+            return Ok(to_value(&JsonResult::NoCommand)?.unchecked_into());
+        }
+
+        let mut streams = self.input_streams()?;
+        let (result, outputs) =
+            match global_yozuk().run_commands(commands, &mut streams, Some(&user)) {
+                Ok(outputs) => (JsonResult::Ok, outputs),
+                Err(outputs) => (JsonResult::Fail, outputs),
+            };
+
+        let value = to_value(&result)?;
+        Reflect::set(&value, &"outputs".into(), &outputs_to_value(&outputs)?)?;
+        Ok(value.unchecked_into())
+    }
 }
 
-fn run(input: JsonInput, buffer: Vec<Box<[u8]>>) -> JsonResult {
-    let mut streams = buffer
-        .into_iter()
-        .map(|data| InputStream::new(Cursor::new(data), media_type!(APPLICATION / OCTET_STREAM)))
-        .collect::<Vec<_>>();
+fn to_value<T: serde::Serialize + ?Sized>(value: &T) -> Result<JsValue, JsValue> {
+    Ok(value.serialize(&Serializer::json_compatible())?)
+}
 
-    let commands = global_yozuk().get_commands(&input.tokens, &streams);
-    if commands.is_empty() {
-        return JsonResult::NoCommand;
+fn outputs_to_value(outputs: &[Output]) -> Result<JsValue, JsValue> {
+    let value = to_value(outputs)?;
+    for (output, output_value) in outputs.iter().zip(js_sys::Array::from(&value).iter()) {
+        let blocks = js_sys::Array::from(&Reflect::get(&output_value, &"blocks".into())?);
+        for (block, block_value) in output.blocks.iter().zip(blocks.iter()) {
+            if let Block::Data(data) = block {
+                set_data_block(data, &block_value)?;
+            }
+        }
     }
+    Ok(value)
+}
 
-    match global_yozuk().run_commands(commands, &mut streams, Some(&input.user)) {
-        Ok(outputs) => JsonResult::Ok { outputs },
-        Err(outputs) => JsonResult::Fail { outputs },
+fn set_data_block(data: &block::Data, value: &JsValue) -> Result<(), JsValue> {
+    if let Ok(text) = str::from_utf8(&data.data) {
+        Reflect::set(value, &"data".into(), &text.into())?;
+        if !data.highlights.is_empty() {
+            let highlights = data
+                .highlights
+                .iter()
+                .map(|highlight| Highlight {
+                    kind: highlight.kind,
+                    range: utf16_offset(text, highlight.range.start)
+                        ..utf16_offset(text, highlight.range.end),
+                })
+                .collect::<Vec<_>>();
+            Reflect::set(value, &"highlights".into(), &to_value(&highlights)?)?;
+        }
+    } else {
+        let bytes = Uint8Array::from(&data.data[..]);
+        if let Some(blob) = new_blob(&bytes, data.media_type.as_str())? {
+            Reflect::set(value, &"blob".into(), &blob)?;
+        }
+        Reflect::set(value, &"data".into(), &bytes)?;
     }
+    Ok(())
+}
+
+fn new_blob(bytes: &Uint8Array, media_type: &str) -> Result<Option<JsValue>, JsValue> {
+    let constructor = Reflect::get(&js_sys::global(), &"Blob".into())?;
+    let constructor = match constructor.dyn_into::<js_sys::Function>() {
+        Ok(constructor) => constructor,
+        Err(_) => return Ok(None),
+    };
+    let options = js_sys::Object::new();
+    Reflect::set(&options, &"type".into(), &media_type.into())?;
+    let args = js_sys::Array::of2(&js_sys::Array::of1(bytes), &options);
+    Ok(Some(Reflect::construct(&constructor, &args)?))
+}
+
+fn utf16_offset(text: &str, offset: usize) -> usize {
+    text.get(..offset)
+        .map(|prefix| prefix.encode_utf16().count())
+        .unwrap_or_else(|| text.encode_utf16().count())
 }
 
 #[derive(Clone, Serialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
-pub enum JsonResult {
-    Ok { outputs: Vec<Output> },
-    Fail { outputs: Vec<Output> },
+enum JsonResult {
+    Ok,
+    Fail,
     NoCommand,
 }
-
-#[derive(Clone, Deserialize)]
-pub struct JsonInput {
-    pub tokens: Vec<Token>,
-    #[serde(default)]
-    pub user: UserContext,
-}
@@ -1,71 +1,44 @@
-import { decode } from 'base64-arraybuffer';
-import { Highlight, Result } from './output'
+import type { Yozuk as Engine, CommandArgs, Result, UserContext } from './wasm-web/yozuk_wasm'
+
+export type Stream = Uint8Array | Blob;
 
 export abstract class YozukBase {
-    protected abstract exec_impl(command: string, user_context: string): Promise<string>;
-    protected abstract push_stream_impl(stream: Uint8Array): Promise<void>;
-    protected abstract random_suggestions_impl(amount: number): Promise<string>;
-    protected abstract push_suggestions_stream_impl(stream: Uint8Array): Promise<void>;
-    protected abstract clear_suggestions_stream_impl(): Promise<void>;
-    protected abstract suggestions_impl(command: string, amount: number): Promise<string>;
+    protected abstract engine(): Promise<Engine>;
     protected abstract user_context(): UserContext;
 
-    async exec(command: string, streams: Uint8Array[] = []): Promise<Result> {
-        for (const stream of streams) {
-            await this.push_stream_impl(stream);
-        }
-        const result: Result = JSON.parse(await this.exec_impl(command, JSON.stringify(this.user_context())));
-        const textDecoder = new TextDecoder('utf-8', { fatal: true });
-        if (result.type == "ok" || result.type == "fail") {
-            for (const output of result.outputs) {
-                for (const block of output.blocks) {
-                    if (block.type == "data") {
-                        if (typeof block.data == "string") {
-                            const decoded = decode(block.data);
-                            try {
-                                block.data = textDecoder.decode(decoded);
-                                if (block.highlights) {
-                                    let newHighlights: Highlight[] = [];
-                                    let byteStart = 0;
-                                    let charStart = 0;
-                                    for (const highlight of block.highlights) {
-                                        charStart += textDecoder.decode(decoded.slice(byteStart, highlight.range.start)).length;
-                                        let charEnd = charStart + textDecoder.decode(decoded.slice(highlight.range.start, highlight.range.end)).length;
-                                        newHighlights.push({ ...highlight, range: { start: charStart, end: charEnd } });
-                                        charStart = charEnd;
-                                        byteStart = highlight.range.end;
-                                    }
-                                    block.highlights = newHighlights;
-                                }
-                            } catch {
-                                block.data = decoded;
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        return result;
+    async getCommands(input: string, streams: Stream[] = []): Promise<CommandArgs[]> {
+        const engine = await this.prepare(streams);
+        return engine.getCommands(input);
     }
 
-    async random_suggestions(amount: number = 5): Promise<String[]> {
-        return JSON.parse(await this.random_suggestions_impl(amount));
+    async runCommands(commands: CommandArgs[], streams: Stream[] = []): Promise<Result> {
+        const engine = await this.prepare(streams);
+        return engine.runCommands(commands, this.user_context());
     }
 
-    async set_suggestions_streams(streams: Uint8Array[]): Promise<void> {
-        await this.clear_suggestions_stream_impl();
-        for (const stream of streams) {
-            await this.push_suggestions_stream_impl(stream);
-        }
+    async exec(input: string, streams: Stream[] = []): Promise<Result> {
+        const engine = await this.prepare(streams);
+        return engine.exec(input, this.user_context());
     }
 
-    async suggestions(command: string, amount: number = 5): Promise<String[]> {
-        return JSON.parse(await this.suggestions_impl(command, amount));
+    async suggest(input: string, amount: number = 5, streams: Stream[] = []): Promise<string[]> {
+        const engine = await this.prepare(streams);
+        return engine.suggest(input, amount);
+    }
+
+    private async prepare(streams: Stream[]): Promise<Engine> {
+        const engine = await this.engine();
+        const buffers = await Promise.all(streams.map(async (stream) =>
+            stream instanceof Uint8Array
+                ? { data: stream, type: undefined }
+                : { data: new Uint8Array(await stream.arrayBuffer()), type: stream.type }
+        ));
+        engine.clearStreams();
+        for (const { data, type } of buffers) {
+            engine.pushStream(data, type);
+        }
+        return engine;
     }
 }
 
-export type UserContext = {
-    username?: string;
-    locale?: string;
-    timezone?: string;
-};
+export type { UserContext };
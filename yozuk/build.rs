@@ -1,27 +1,33 @@
 use anyhow::Result;
-use std::io::Write;
-use std::{env, fs::File, io::Read, path::Path};
-use yozuk_sdk::model::*;
+use std::{env, fs, path::Path};
+use yozuk_model::{modelgen_with_options, ModelgenOptions};
 use yozuk_sdk::prelude::*;
 
+const PRUNE_THRESHOLD: f64 = 0.01;
+
 fn main() -> Result<()> {
+    // Skill sources live in yozuk-core-skillset, a build dependency, so editing
+    // them rebuilds and reruns this script. Edits to this crate do not.
+    println!("cargo:rerun-if-changed=build.rs");
+
     let out_dir = env::var_os("OUT_DIR").unwrap();
     let model_path = Path::new(&out_dir).join("model.data");
+    let cache_dir = Path::new(&out_dir).join("model-cache");
 
-    if let Ok(mut file) = File::open(&model_path) {
-        let mut data = Vec::new();
-        let _ = file.read_to_end(&mut data);
-        if data.ends_with(&yozuk_core_skillset::skills_digest())
-            && ModelSet::from_data(data).is_ok()
-        {
-            return Ok(());
-        }
-    }
+    let options = ModelgenOptions {
+        cache_dir: Some(cache_dir),
+        prune_threshold: PRUNE_THRESHOLD,
+        compress: true,
+    };
+    let model = modelgen_with_options(yozuk_core_skillset::SKILLS, &Environment::new(), &options)?;
 
-    let model = yozuk_model::modelgen(yozuk_core_skillset::SKILLS, &Environment::new())?;
-    let mut file = File::create(model_path)?;
-    model.write(&mut file)?;
-    file.write_all(&yozuk_core_skillset::skills_digest())?;
+    let mut data = Vec::new();
+    model.write(&mut data)?;
+    data.extend_from_slice(&yozuk_core_skillset::skills_digest());
+
+    if fs::read(&model_path).ok().as_ref() != Some(&data) {
+        fs::write(model_path, data)?;
+    }
 
     Ok(())
 }
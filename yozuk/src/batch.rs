@@ -0,0 +1,45 @@
+use super::Yozuk;
+use yozuk_sdk::prelude::*;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchResult {
+    Ok(Vec<Output>),
+    Fail(Vec<Output>),
+    NoCommand,
+}
+
+impl From<Result<Vec<Output>, Vec<Output>>> for BatchResult {
+    fn from(result: Result<Vec<Output>, Vec<Output>>) -> Self {
+        match result {
+            Ok(outputs) => Self::Ok(outputs),
+            Err(outputs) => Self::Fail(outputs),
+        }
+    }
+}
+
+impl Yozuk {
+    pub fn run_batch<T>(&self, queries: &[T], user: Option<&UserContext>) -> Vec<BatchResult>
+    where
+        T: AsRef<str> + Sync,
+    {
+        #[cfg(feature = "rayon")]
+        let iter = queries.par_iter();
+        #[cfg(not(feature = "rayon"))]
+        let iter = queries.iter();
+
+        iter.map(|query| self.run_query(query.as_ref(), user))
+            .collect()
+    }
+
+    fn run_query(&self, query: &str, user: Option<&UserContext>) -> BatchResult {
+        let tokens = Tokenizer::new().tokenize(query);
+        let commands = self.get_commands(&tokens, &[]);
+        if commands.is_empty() {
+            return BatchResult::NoCommand;
+        }
+        self.run_commands(commands, &mut [], user).into()
+    }
+}
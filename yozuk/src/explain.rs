@@ -0,0 +1,70 @@
+use super::Yozuk;
+use yozuk_model::{domain_confidence, FeatureLabeler};
+use yozuk_sdk::prelude::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    pub skill: String,
+    pub tokens: Vec<Token>,
+    pub confidence: f64,
+    pub command: Option<CommandArgs>,
+}
+
+impl Yozuk {
+    pub fn skills(&self) -> impl Iterator<Item = &str> {
+        self.commands
+            .iter()
+            .filter_map(|cache| cache.as_ref())
+            .map(|cache| cache.name)
+    }
+
+    pub fn explain(&self, tokens: &[Token], streams: &[InputStream]) -> Vec<Explanation> {
+        let redirection = self.redirection(tokens);
+        let labeler = FeatureLabeler::new(&self.labelers);
+        let mut explanations = self
+            .commands
+            .iter()
+            .filter_map(|cache| cache.as_ref())
+            .map(|cache| {
+                let tokens = cache.tag_tokens(&labeler, tokens);
+                let confidence = domain_confidence(&tokens);
+                let command = if redirection.is_none() && confidence >= self.out_of_domain_threshold
+                {
+                    cache.generate_command(&tokens, streams)
+                } else {
+                    None
+                };
+                let explanation = Explanation {
+                    skill: cache.name.to_string(),
+                    tokens,
+                    confidence,
+                    command,
+                };
+                (cache.command.priority(), explanation)
+            })
+            .collect::<Vec<_>>();
+
+        // Skills with a command come first, in the same order as get_commands.
+        explanations.sort_by(|(a_priority, a), (b_priority, b)| {
+            b.command
+                .is_some()
+                .cmp(&a.command.is_some())
+                .then(if a.command.is_some() {
+                    b_priority.cmp(a_priority)
+                } else {
+                    b.confidence.total_cmp(&a.confidence)
+                })
+        });
+
+        redirection
+            .map(|command| Explanation {
+                skill: command.args[0].clone(),
+                tokens: tokens.to_vec(),
+                confidence: 1.0,
+                command: Some(command),
+            })
+            .into_iter()
+            .chain(explanations.into_iter().map(|(_, explanation)| explanation))
+            .collect()
+    }
+}
@@ -1,13 +1,15 @@
 #![forbid(unsafe_code)]
 #![deny(clippy::all)]
 
+use bytes::Bytes;
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
 use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
 use rand::SeedableRng;
+use std::collections::BTreeSet;
 use std::{iter, mem};
-use yozuk_model::FeatureLabeler;
+use yozuk_model::{domain_confidence, FeatureLabeler, DEFAULT_OUT_OF_DOMAIN_THRESHOLD};
 use yozuk_sdk::model::*;
 use yozuk_sdk::prelude::*;
 
@@ -17,14 +19,36 @@ use rayon::prelude::*;
 #[cfg(all(feature = "rayon", target_arch = "wasm32"))]
 compile_error!("wasm target does not support rayon");
 
+mod batch;
+mod explain;
 mod model;
 mod skill;
 
+pub use batch::*;
+pub use explain::*;
 pub use model::*;
 pub use skill::*;
 
 pub const MODEL_DATA: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/model.data"));
 
+pub fn is_compatible_model(data: &[u8]) -> bool {
+    load_model(Bytes::copy_from_slice(data)).is_some()
+}
+
+pub fn load_model<T>(data: T) -> Option<ModelSet>
+where
+    T: Into<Bytes>,
+{
+    let data = data.into();
+    if data.ends_with(&skills_digest()) {
+        ModelSet::from_data(data)
+            .ok()
+            .filter(|model| model.validate().is_ok())
+    } else {
+        None
+    }
+}
+
 const MAX_ARG_BYTES_LEN: usize = 10240;
 
 pub struct Yozuk {
@@ -33,6 +57,7 @@ pub struct Yozuk {
     labelers: Vec<Box<dyn Labeler>>,
     commands: Vec<Option<CommandCache>>,
     redirections: Vec<(Vec<Token>, Vec<String>)>,
+    out_of_domain_threshold: f64,
     seed: u64,
 }
 
@@ -42,25 +67,8 @@ impl Yozuk {
     }
 
     pub fn get_commands(&self, tokens: &[Token], streams: &[InputStream]) -> Vec<CommandArgs> {
-        let filter = |(redirect, _): &&(Vec<Token>, Vec<String>)| {
-            redirect.len() == tokens.len()
-                && redirect
-                    .iter()
-                    .map(|token| token.as_str())
-                    .zip(tokens.iter().map(|token| token.as_str()))
-                    .all(|(a, b)| yozuk_helper_english::normalized_eq(a, [b], 0))
-        };
-
-        #[cfg(feature = "rayon")]
-        let redirection = self.redirections.par_iter().find_first(filter);
-
-        #[cfg(not(feature = "rayon"))]
-        let redirection = self.redirections.iter().find(filter);
-
-        if let Some((_, args)) = redirection {
-            return vec![CommandArgs::new()
-                .add_args(["yozuk-redirect"])
-                .add_args_iter(args)];
+        if let Some(redirection) = self.redirection(tokens) {
+            return vec![redirection];
         }
 
         let labeler = FeatureLabeler::new(&self.labelers);
@@ -72,41 +80,12 @@ impl Yozuk {
 
         let mut commands = iter
             .filter_map(|cache| cache.as_ref())
-            .map(|cache| {
-                (
-                    cache,
-                    cache
-                        .preprocessors
-                        .iter()
-                        .fold(tokens.to_vec(), |tokens, prep| prep.preprocess(tokens)),
-                )
-            })
-            .map(|(cache, tokens)| {
-                (
-                    cache,
-                    if let Some(model) = &cache.model {
-                        model.tag_tokens(&labeler, &tokens)
-                    } else {
-                        tokens
-                    },
-                )
-            })
+            .map(|cache| (cache, cache.tag_tokens(&labeler, tokens)))
+            .filter(|(_, args)| domain_confidence(args) >= self.out_of_domain_threshold)
             .filter_map(|(cache, args)| {
                 cache
-                    .translators
-                    .iter()
-                    .find_map(|tr| tr.generate_command(&args, streams))
-                    .map(|args| {
-                        (
-                            cache.command.priority(),
-                            CommandArgs {
-                                args: iter::once(cache.name.to_string())
-                                    .chain(args.args.into_iter())
-                                    .collect(),
-                                data: args.data,
-                            },
-                        )
-                    })
+                    .generate_command(&args, streams)
+                    .map(|args| (cache.command.priority(), args))
             })
             .collect::<Vec<_>>();
 
@@ -114,6 +93,29 @@ impl Yozuk {
         commands.into_iter().map(|command| command.1).collect()
     }
 
+    fn redirection(&self, tokens: &[Token]) -> Option<CommandArgs> {
+        let filter = |(redirect, _): &&(Vec<Token>, Vec<String>)| {
+            redirect.len() == tokens.len()
+                && redirect
+                    .iter()
+                    .map(|token| token.as_str())
+                    .zip(tokens.iter().map(|token| token.as_str()))
+                    .all(|(a, b)| yozuk_helper_english::normalized_eq(a, [b], 0))
+        };
+
+        #[cfg(feature = "rayon")]
+        let redirection = self.redirections.par_iter().find_first(filter);
+
+        #[cfg(not(feature = "rayon"))]
+        let redirection = self.redirections.iter().find(filter);
+
+        redirection.map(|(_, args)| {
+            CommandArgs::new()
+                .add_args(["yozuk-redirect"])
+                .add_args_iter(args)
+        })
+    }
+
     pub fn run_commands(
         &self,
         commands: Vec<CommandArgs>,
@@ -162,6 +164,14 @@ impl Yozuk {
         }
     }
 
+    pub fn skill_title(&self, key: &str) -> Option<&str> {
+        self.model
+            .get_index(key)
+            .and_then(|index| self.commands.get(index)?.as_ref())
+            .map(|cache| cache.title.as_str())
+            .filter(|title| !title.is_empty())
+    }
+
     pub fn random_suggestions(&self, amount: usize) -> Vec<String> {
         let mut suggestions = Vec::with_capacity(amount);
         let mut skills = self
@@ -202,25 +212,7 @@ impl Yozuk {
 
         let mut suggestions = iter
             .filter_map(|cache| cache.as_ref())
-            .map(|cache| {
-                (
-                    cache,
-                    cache
-                        .preprocessors
-                        .iter()
-                        .fold(args.to_vec(), |tokens, prep| prep.preprocess(tokens)),
-                )
-            })
-            .map(|(cache, tokens)| {
-                (
-                    cache,
-                    if let Some(model) = &cache.model {
-                        model.tag_tokens(&labeler, &tokens)
-                    } else {
-                        tokens
-                    },
-                )
-            })
+            .map(|cache| (cache, cache.tag_tokens(&labeler, args)))
             .flat_map(|(cache, tokens)| {
                 cache
                     .suggestions
@@ -246,11 +238,35 @@ impl Yozuk {
             .map(|(_, _, _, text)| text)
             .collect()
     }
+
+    pub fn completions(&self, input: &str, pos: usize, amount: usize) -> (usize, Vec<String>) {
+        let head = input.get(..pos).unwrap_or(input);
+        let start = head
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map(|(index, c)| index + c.len_utf8())
+            .unwrap_or(0);
+        let prefix = head[start..].to_lowercase();
+
+        let candidates = self
+            .commands
+            .iter()
+            .filter_map(|cache| cache.as_ref())
+            .flat_map(|cache| &cache.completions)
+            .flat_map(|skill| skill.completions())
+            .filter(|word| word.to_lowercase().starts_with(&prefix))
+            .collect::<BTreeSet<_>>();
+
+        (start, candidates.into_iter().take(amount).collect())
+    }
 }
 
 pub struct YozukBuilder {
     user_context: UserContext,
     redirections: Vec<(Vec<Token>, Vec<String>)>,
+    model: Option<ModelSet>,
+    out_of_domain_threshold: f64,
 }
 
 impl Default for YozukBuilder {
@@ -262,6 +278,8 @@ impl Default for YozukBuilder {
                 ..Default::default()
             },
             redirections: vec![],
+            model: None,
+            out_of_domain_threshold: DEFAULT_OUT_OF_DOMAIN_THRESHOLD,
         }
     }
 }
@@ -272,6 +290,24 @@ impl YozukBuilder {
         self
     }
 
+    pub fn set_model_data<T>(mut self, data: T) -> Self
+    where
+        T: Into<Bytes>,
+    {
+        self.model = load_model(data);
+        self
+    }
+
+    pub fn set_model(mut self, model: ModelSet) -> Self {
+        self.model = Some(model);
+        self
+    }
+
+    pub fn set_out_of_domain_threshold(mut self, threshold: f64) -> Self {
+        self.out_of_domain_threshold = threshold;
+        self
+    }
+
     pub fn add_redirection<T, TI, S, SI>(mut self, tokens: TI, args: SI) -> Self
     where
         T: Into<Token>,
@@ -287,7 +323,9 @@ impl YozukBuilder {
     }
 
     pub fn build(self) -> Yozuk {
-        let model = ModelSet::from_data(MODEL_DATA).unwrap();
+        let model = self
+            .model
+            .unwrap_or_else(|| ModelSet::from_data(MODEL_DATA).unwrap());
         let build_info = concat!(r#"{"version": ""#, env!("CARGO_PKG_VERSION"), r#""}"#);
 
         let env = Environment::new().build_info(build_info);
@@ -320,10 +358,15 @@ impl YozukBuilder {
                     }
                     commands[index] = Some(CommandCache {
                         name: entry.key,
-                        model: model.get(entry.key).map(ModelEntry::new),
+                        title: mem::take(&mut skill.title),
+                        model: model.get(entry.key).map(|data| {
+                            let features = model.features(entry.key).unwrap_or(skill.features);
+                            ModelEntry::new(data, features)
+                        }),
                         translators: mem::take(&mut skill.translators),
                         preprocessors: mem::take(&mut skill.preprocessors),
                         suggestions: mem::take(&mut skill.suggestions),
+                        completions: mem::take(&mut skill.completions),
                         command,
                     });
                 }
@@ -336,6 +379,7 @@ impl YozukBuilder {
             labelers,
             commands,
             redirections: self.redirections,
+            out_of_domain_threshold: self.out_of_domain_threshold,
             seed: rand::random(),
         }
     }
@@ -343,9 +387,35 @@ impl YozukBuilder {
 
 struct CommandCache {
     name: &'static str,
+    title: String,
     model: Option<ModelEntry>,
     preprocessors: Vec<Box<dyn Preprocessor>>,
     translators: Vec<Box<dyn Translator>>,
     suggestions: Vec<Box<dyn Suggestions>>,
+    completions: Vec<Box<dyn Completions>>,
     command: Box<dyn Command>,
 }
+
+impl CommandCache {
+    fn tag_tokens(&self, labeler: &FeatureLabeler, tokens: &[Token]) -> Vec<Token> {
+        let tokens = self
+            .preprocessors
+            .iter()
+            .fold(tokens.to_vec(), |tokens, prep| prep.preprocess(tokens));
+        if let Some(model) = &self.model {
+            model.tag_tokens(labeler, &tokens)
+        } else {
+            tokens
+        }
+    }
+
+    fn generate_command(&self, tokens: &[Token], streams: &[InputStream]) -> Option<CommandArgs> {
+        self.translators
+            .iter()
+            .find_map(|tr| tr.generate_command(tokens, streams))
+            .map(|args| CommandArgs {
+                args: iter::once(self.name.to_string()).chain(args.args).collect(),
+                data: args.data,
+            })
+    }
+}
@@ -5,12 +5,14 @@ use yozuk_sdk::prelude::*;
 
 pub struct ModelEntry {
     tagger: Tagger,
+    features: FeatureSet,
 }
 
 impl ModelEntry {
-    pub fn new(data: Bytes) -> Self {
+    pub fn new(data: Bytes, features: FeatureSet) -> Self {
         Self {
             tagger: Tagger::new(data),
+            features,
         }
     }
 
@@ -23,7 +25,7 @@ impl ModelEntry {
     }
 
     pub fn tag_tokens(&self, labeler: &FeatureLabeler, tokens: &[Token]) -> Vec<Token> {
-        let features = labeler.label_features(tokens);
+        let features = labeler.label_features_with(tokens, self.features);
         let tags = self.tag(&features);
 
         tokens
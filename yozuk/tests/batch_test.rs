@@ -0,0 +1,23 @@
+#![cfg(all(feature = "yozuk-skill-calc", feature = "yozuk-skill-digest"))]
+
+mod common;
+use common::yozuk_global;
+use yozuk::BatchResult;
+use yozuk_sdk::prelude::*;
+
+#[test]
+fn test_batch() {
+    let results = yozuk_global().run_batch(&["1 + 1", "Hello World to md5", ""], None);
+    assert_eq!(results.len(), 3);
+    assert_eq!(
+        results[0],
+        BatchResult::Ok(vec![Output::new()
+            .set_title("Calculator")
+            .add_block(block::Data::new().set_text_data("2"))
+            .add_metadata(
+                Metadata::docs("https://docs.yozuk.com/docs/skills/calc/").unwrap()
+            )])
+    );
+    assert!(matches!(&results[1], BatchResult::Ok(outputs) if outputs[0].title == "Digest"));
+    assert_eq!(results[2], BatchResult::NoCommand);
+}
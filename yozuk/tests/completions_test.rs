@@ -0,0 +1,25 @@
+mod common;
+use common::yozuk_global;
+
+#[test]
+fn test_completions() {
+    assert_eq!(
+        yozuk_global().completions("Hello to md", 11, 10),
+        (9, vec!["md4".to_string(), "md5".to_string()])
+    );
+    assert_eq!(
+        yozuk_global().completions("Hello to MD", 11, 10),
+        (9, vec!["md4".to_string(), "md5".to_string()])
+    );
+    assert_eq!(
+        yozuk_global().completions("Hello to md5", 12, 10),
+        (9, vec!["md5".to_string()])
+    );
+    assert_eq!(yozuk_global().completions("10 to md", 5, 10).0, 3);
+    assert!(yozuk_global()
+        .completions("now to rfc", 10, 10)
+        .1
+        .iter()
+        .all(|word| word.starts_with("rfc")));
+    assert_eq!(yozuk_global().completions("", 0, 0).1.len(), 0);
+}
@@ -20,6 +20,18 @@ fn simple() {
     );
 }
 
+#[test]
+fn simple_variants() {
+    assert_eq!(
+        cmd(tk!(["could", "you", "roll", "dice"])),
+        Some(CommandArgs::new().add_args(["yozuk-skill-dice", "1d6"]))
+    );
+    assert_eq!(
+        cmd(tk!(["rol", "dice"])),
+        Some(CommandArgs::new().add_args(["yozuk-skill-dice", "1d6"]))
+    );
+}
+
 #[test]
 fn simple_notation() {
     assert_eq!(
@@ -0,0 +1,47 @@
+#![cfg(all(feature = "yozuk-skill-calc", feature = "yozuk-skill-uuid"))]
+
+mod common;
+use common::yozuk_global;
+use yozuk_sdk::prelude::*;
+
+#[test]
+fn explain() {
+    let zuk = yozuk_global();
+    let tokens = tk!(["1", "+", "1"]);
+    let commands = zuk.get_commands(&tokens, &[]);
+    let explanations = zuk.explain(&tokens, &[]);
+    assert_eq!(explanations.len(), zuk.skills().count());
+
+    let explained = explanations
+        .iter()
+        .filter_map(|exp| exp.command.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(explained, commands);
+
+    let calc = explanations
+        .iter()
+        .find(|exp| exp.skill == "yozuk-skill-calc")
+        .unwrap();
+    assert_eq!(calc.tokens.len(), 1);
+    assert!(calc.confidence >= 0.5);
+}
+
+#[test]
+fn explain_redirection() {
+    let zuk = yozuk_global();
+    let tokens = tk!(["test", "command", "redirect"]);
+    let commands = zuk.get_commands(&tokens, &[]);
+    let explained = zuk
+        .explain(&tokens, &[])
+        .into_iter()
+        .filter_map(|exp| exp.command)
+        .collect::<Vec<_>>();
+    assert_eq!(explained, commands);
+}
+
+#[test]
+fn skills() {
+    let zuk = yozuk_global();
+    assert!(zuk.skills().any(|skill| skill == "yozuk-skill-uuid"));
+    assert!(zuk.skills().all(|skill| zuk.skill_title(skill).is_some()));
+}
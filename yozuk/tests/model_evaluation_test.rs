@@ -0,0 +1,111 @@
+use yozuk_model::Split;
+use yozuk_sdk::prelude::*;
+
+const MINIMUM_ACCURACY: f64 = 0.9;
+const MINIMUM_SUBSET_ACCURACY: f64 = 0.95;
+
+const SUBSET: &[&str] = &[
+    "yozuk-skill-color",
+    "yozuk-skill-compression",
+    "yozuk-skill-kdf",
+    "yozuk-skill-nanoid",
+    "yozuk-skill-qrcode",
+    "yozuk-skill-username",
+    "yozuk-skill-uuid",
+];
+
+#[test]
+fn model_accuracy_subset() {
+    let skills = yozuk::SKILLS
+        .iter()
+        .filter(|item| SUBSET.contains(&item.key))
+        .cloned()
+        .collect::<Vec<_>>();
+    let report = yozuk_model::evaluate(
+        &skills,
+        &Environment::new(),
+        Split::HoldOut { test_ratio: 0.2 },
+        0,
+    )
+    .unwrap();
+    println!("{}", report);
+    assert!(
+        report.accuracy() >= MINIMUM_SUBSET_ACCURACY,
+        "accuracy regressed: {:.3} < {:.3}",
+        report.accuracy(),
+        MINIMUM_SUBSET_ACCURACY
+    );
+}
+
+#[test]
+#[ignore]
+fn model_accuracy() {
+    let report = yozuk_model::evaluate(
+        yozuk::SKILLS,
+        &Environment::new(),
+        Split::HoldOut { test_ratio: 0.2 },
+        0,
+    )
+    .unwrap();
+    println!("{}", report);
+    assert!(
+        report.accuracy() >= MINIMUM_ACCURACY,
+        "accuracy regressed: {:.3} < {:.3}",
+        report.accuracy(),
+        MINIMUM_ACCURACY
+    );
+}
+
+#[test]
+#[ignore]
+fn feature_ablation() {
+    let none = FeatureSet::default();
+    for (name, features) in [
+        ("baseline", none),
+        (
+            "char_ngrams",
+            FeatureSet {
+                char_ngrams: true,
+                ..none
+            },
+        ),
+        (
+            "word_shape",
+            FeatureSet {
+                word_shape: true,
+                ..none
+            },
+        ),
+        (
+            "position",
+            FeatureSet {
+                position: true,
+                ..none
+            },
+        ),
+        ("all", FeatureSet::ALL),
+    ] {
+        let report = yozuk_model::evaluate_with_features(
+            yozuk::SKILLS,
+            &Environment::new(),
+            Split::HoldOut { test_ratio: 0.2 },
+            0,
+            features,
+        )
+        .unwrap();
+        let consts = report
+            .skills()
+            .into_iter()
+            .find(|skill| skill.key == "yozuk-skill-consts")
+            .unwrap();
+        println!(
+            "{:12} accuracy: {:.3} ({}/{})  consts precision: {:.3} recall: {:.3}",
+            name,
+            report.accuracy(),
+            report.correct(),
+            report.total(),
+            consts.precision,
+            consts.recall
+        );
+    }
+}
@@ -0,0 +1,68 @@
+#![cfg(feature = "yozuk-skill-calc")]
+
+use yozuk::Yozuk;
+use yozuk_sdk::model::ModelSet;
+use yozuk_sdk::prelude::*;
+
+#[test]
+fn test_model_compatibility() {
+    assert!(yozuk::is_compatible_model(yozuk::MODEL_DATA));
+    assert!(!yozuk::is_compatible_model(b""));
+    assert!(!yozuk::is_compatible_model(
+        &yozuk::MODEL_DATA[..yozuk::MODEL_DATA.len() - 1]
+    ));
+
+    let mut truncated = yozuk::MODEL_DATA[..yozuk::MODEL_DATA.len() / 2].to_vec();
+    truncated.extend_from_slice(&yozuk::skills_digest());
+    assert!(!yozuk::is_compatible_model(&truncated));
+}
+
+#[test]
+fn test_external_model() {
+    let zuk = Yozuk::builder()
+        .set_model_data(yozuk::MODEL_DATA.to_vec())
+        .build();
+    assert_eq!(
+        zuk.get_commands(&Tokenizer::new().tokenize("1 + 1"), &[])
+            .into_iter()
+            .next(),
+        Some(CommandArgs::new().add_args(["yozuk-skill-calc", "1+1"]))
+    );
+}
+
+#[test]
+fn test_load_model() {
+    let model = yozuk::load_model(yozuk::MODEL_DATA).unwrap();
+    let zuk = Yozuk::builder().set_model(model).build();
+    assert_eq!(
+        zuk.get_commands(&Tokenizer::new().tokenize("1 + 1"), &[])
+            .into_iter()
+            .next(),
+        Some(CommandArgs::new().add_args(["yozuk-skill-calc", "1+1"]))
+    );
+    assert!(yozuk::load_model(&b"invalid"[..]).is_none());
+}
+
+#[test]
+fn test_incompatible_model_fallback() {
+    let zuk = Yozuk::builder().set_model_data(&b"invalid"[..]).build();
+    assert_eq!(
+        zuk.get_commands(&Tokenizer::new().tokenize("1 + 1"), &[])
+            .into_iter()
+            .next(),
+        Some(CommandArgs::new().add_args(["yozuk-skill-calc", "1+1"]))
+    );
+}
+
+#[test]
+fn test_model_size_report() {
+    let model = ModelSet::from_data(yozuk::MODEL_DATA).unwrap();
+    let report = yozuk_model::SizeReport::new(&model);
+    println!("{}", report);
+    assert!(model.is_compressed());
+    assert_eq!(
+        model.features("yozuk-skill-calc"),
+        Some(FeatureSet::default())
+    );
+    assert!(report.stored() < report.uncompressed());
+}
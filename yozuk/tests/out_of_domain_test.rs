@@ -0,0 +1,20 @@
+use yozuk::Yozuk;
+use yozuk_sdk::prelude::*;
+
+#[test]
+fn test_out_of_domain() {
+    let zuk = Yozuk::builder().build();
+    for query in [
+        "How deep is the ocean",
+        "Book a hotel in Berlin",
+        "Remind me to call the plumber",
+        "What should I eat for lunch",
+    ] {
+        assert_eq!(
+            zuk.get_commands(&Tokenizer::new().tokenize(query), &[]),
+            vec![],
+            "{}",
+            query
+        );
+    }
+}
@@ -23,3 +23,19 @@ fn test_password_generation() {
         Some(CommandArgs::new().add_args(["yozuk-skill-password"]))
     );
 }
+
+#[test]
+fn test_password_generation_variants() {
+    assert_eq!(
+        cmd(tk!(["can", "you", "generate", "password"])),
+        Some(CommandArgs::new().add_args(["yozuk-skill-password"]))
+    );
+    assert_eq!(
+        cmd(tk!(["genrate", "password"])),
+        Some(CommandArgs::new().add_args(["yozuk-skill-password"]))
+    );
+    assert_eq!(
+        cmd(tk!(["generate", "pwgen"])),
+        Some(CommandArgs::new().add_args(["yozuk-skill-password"]))
+    );
+}
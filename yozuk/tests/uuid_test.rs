@@ -63,3 +63,12 @@ fn lowercase_uuid() {
         Some(CommandArgs::new().add_args(["yozuk-skill-uuid", "-n", "1"]))
     );
 }
+
+#[test]
+fn title() {
+    assert_eq!(
+        common::yozuk_global().skill_title("yozuk-skill-uuid"),
+        Some("UUID Generator")
+    );
+    assert_eq!(common::yozuk_global().skill_title("yozuk-skill-none"), None);
+}
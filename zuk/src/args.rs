@@ -1,6 +1,9 @@
-use clap::Parser;
+use clap::{ArgEnum, Parser};
 use std::path::PathBuf;
 
+#[cfg(all(feature = "rpc", not(target_arch = "wasm32")))]
+use crate::rpc;
+
 /// Chatbot for Programmers
 #[derive(Parser)]
 #[clap(version, trailing_var_arg = true)]
@@ -25,11 +28,48 @@ pub struct Args {
     #[clap(short, long, parse(from_occurrences))]
     pub verbose: usize,
 
+    /// Pick the interpretation of the given skill when several skills match
+    #[clap(long)]
+    pub pick: Option<String>,
+
+    /// Output format
+    #[clap(arg_enum, long, default_value = "text")]
+    pub format: OutputFormat,
+
+    /// Write data blocks to a file or a directory
+    #[clap(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Do not pipe long outputs through a pager
+    ///
+    /// $PAGER is used when set, except when zuk is sandboxed (the secure-context
+    /// feature, enabled by default on Linux x86_64), which cannot spawn processes.
+    #[clap(long)]
+    pub no_pager: bool,
+
+    /// Load model data from a file instead of the embedded one
+    #[clap(long)]
+    pub model: Option<PathBuf>,
+
+    /// Run queries from a file line by line and print the results as JSON Lines
+    #[clap(long)]
+    pub batch: Option<PathBuf>,
+
     /// Start RPC server
     #[cfg(all(feature = "rpc", not(target_arch = "wasm32")))]
     #[clap(long)]
     pub rpc: bool,
 
+    /// Start RPC server on a Unix domain socket (unix:PATH) or a local TCP address (HOST:PORT)
+    #[cfg(all(feature = "rpc", not(target_arch = "wasm32")))]
+    #[clap(long, value_name = "ADDR")]
+    pub rpc_listen: Option<rpc::Endpoint>,
+
+    /// Serve the API over HTTP on the given address (e.g. 127.0.0.1:8080)
+    #[cfg(all(feature = "http-server", not(target_arch = "wasm32")))]
+    #[clap(long, value_name = "ADDR")]
+    pub serve: Option<String>,
+
     /// Show suggestions.
     #[cfg(debug_assertions)]
     #[clap(long, default_value = "0", display_order(1000))]
@@ -39,4 +79,16 @@ pub struct Args {
     #[cfg(debug_assertions)]
     #[clap(long, display_order(1000))]
     pub dump_model: Option<PathBuf>,
+
+    /// Dump preprocessed training sentences as JSON Lines.
+    #[cfg(all(debug_assertions, feature = "dump-corpus"))]
+    #[clap(long, display_order(1000))]
+    pub dump_corpus: Option<Option<String>>,
+}
+
+#[derive(ArgEnum, Copy, Clone, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Raw,
 }
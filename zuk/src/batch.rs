@@ -0,0 +1,97 @@
+use anyhow::Result;
+use serde_derive::Serialize;
+use std::io::{BufRead, Write};
+use yozuk::{BatchResult, Yozuk};
+use yozuk_sdk::prelude::*;
+
+const CHUNK_SIZE: usize = 256;
+
+pub fn run_batch<R, W>(zuk: &Yozuk, reader: R, mut writer: W) -> Result<()>
+where
+    R: BufRead,
+    W: Write,
+{
+    let mut queries = Vec::with_capacity(CHUNK_SIZE);
+    for line in reader.lines() {
+        queries.push(line?);
+        if queries.len() >= CHUNK_SIZE {
+            write_results(zuk, &queries, &mut writer)?;
+            queries.clear();
+        }
+    }
+    write_results(zuk, &queries, &mut writer)
+}
+
+fn write_results<W>(zuk: &Yozuk, queries: &[String], mut writer: W) -> Result<()>
+where
+    W: Write,
+{
+    let results = zuk.run_batch(queries, None);
+    for (query, result) in queries.iter().zip(results) {
+        let line = match result {
+            BatchResult::Ok(outputs) => BatchLine::Ok { query, outputs },
+            BatchResult::Fail(outputs) => BatchLine::Fail { query, outputs },
+            BatchResult::NoCommand => BatchLine::NoCommand { query },
+        };
+        serde_json::to_writer(&mut writer, &line)?;
+        writeln!(&mut writer)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum BatchLine<'a> {
+    Ok {
+        query: &'a str,
+        outputs: Vec<Output>,
+    },
+    Fail {
+        query: &'a str,
+        outputs: Vec<Output>,
+    },
+    NoCommand {
+        query: &'a str,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_batch() {
+        let input = Cursor::new("Hello World to md5\n\n???\n");
+        let mut output = Vec::<u8>::new();
+        let zuk = Yozuk::builder().build();
+        run_batch(&zuk, input, &mut output).unwrap();
+
+        let lines = String::from_utf8(output).unwrap();
+        let lines = lines
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["type"], json!("ok"));
+        assert_eq!(lines[0]["query"], json!("Hello World to md5"));
+        assert_eq!(lines[0]["outputs"][0]["title"], json!("Digest"));
+        assert_eq!(
+            lines[1],
+            json!({
+                "type": "no_command",
+                "query": ""
+            })
+        );
+        assert_eq!(
+            lines[2],
+            json!({
+                "type": "no_command",
+                "query": "???"
+            })
+        );
+    }
+}
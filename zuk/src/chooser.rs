@@ -0,0 +1,128 @@
+use anyhow::Result;
+use owo_colors::OwoColorize;
+use std::io::{BufRead, Write};
+use yozuk::Yozuk;
+use yozuk_sdk::prelude::*;
+
+pub const SKILL_PREFIX: &str = "yozuk-skill-";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Choice {
+    One(usize),
+    All,
+    Cancel,
+}
+
+pub fn skill_name(key: &str) -> &str {
+    key.strip_prefix(SKILL_PREFIX).unwrap_or(key)
+}
+
+pub fn pick(commands: &[CommandArgs], skill: &str) -> Vec<CommandArgs> {
+    commands
+        .iter()
+        .filter(|cmd| cmd.args[0] == skill || skill_name(&cmd.args[0]) == skill)
+        .cloned()
+        .collect()
+}
+
+pub fn choose<R, W>(
+    zuk: &Yozuk,
+    mut commands: Vec<CommandArgs>,
+    mut input: R,
+    mut output: W,
+) -> Result<Vec<CommandArgs>>
+where
+    R: BufRead,
+    W: Write,
+{
+    writeln!(output, "Several skills can handle your request:")?;
+    for (i, cmd) in commands.iter().enumerate() {
+        let name = skill_name(&cmd.args[0]);
+        writeln!(
+            output,
+            "{:>3}  {}  {}",
+            i + 1,
+            zuk.skill_title(&cmd.args[0]).unwrap_or(name).bold(),
+            format!("--pick {}", name).dimmed()
+        )?;
+    }
+
+    loop {
+        write!(
+            output,
+            "Choose [1-{}, a = all, q = cancel] (1): ",
+            commands.len()
+        )?;
+        output.flush()?;
+
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(vec![]);
+        }
+        match parse_choice(&line, commands.len()) {
+            Some(Choice::One(index)) => return Ok(vec![commands.swap_remove(index)]),
+            Some(Choice::All) => return Ok(commands),
+            Some(Choice::Cancel) => return Ok(vec![]),
+            None => writeln!(output, "{}", "Invalid choice.".red())?,
+        }
+    }
+}
+
+fn parse_choice(input: &str, len: usize) -> Option<Choice> {
+    match input.trim() {
+        "" => Some(Choice::One(0)),
+        "a" | "all" => Some(Choice::All),
+        "q" | "quit" => Some(Choice::Cancel),
+        n => n
+            .parse::<usize>()
+            .ok()
+            .filter(|n| (1..=len).contains(n))
+            .map(|n| Choice::One(n - 1)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_choice() {
+        assert_eq!(parse_choice("\n", 2), Some(Choice::One(0)));
+        assert_eq!(parse_choice(" 2\n", 2), Some(Choice::One(1)));
+        assert_eq!(parse_choice("a\n", 2), Some(Choice::All));
+        assert_eq!(parse_choice("q\n", 2), Some(Choice::Cancel));
+        assert_eq!(parse_choice("3\n", 2), None);
+        assert_eq!(parse_choice("0\n", 2), None);
+    }
+
+    #[test]
+    fn test_pick() {
+        let commands = [
+            CommandArgs::new().add_args(["yozuk-skill-uuid", "-n", "1"]),
+            CommandArgs::new().add_args(["yozuk-skill-nanoid"]),
+        ];
+        assert_eq!(pick(&commands, "nanoid"), [commands[1].clone()]);
+        assert_eq!(pick(&commands, "yozuk-skill-uuid"), [commands[0].clone()]);
+        assert!(pick(&commands, "dice").is_empty());
+    }
+
+    #[test]
+    fn test_choose() -> Result<()> {
+        let zuk = Yozuk::builder().build();
+        let commands = vec![
+            CommandArgs::new().add_args(["yozuk-skill-uuid", "-n", "1"]),
+            CommandArgs::new().add_args(["yozuk-skill-nanoid"]),
+        ];
+        let mut output = Vec::new();
+        let chosen = choose(&zuk, commands.clone(), &b"x\n2\n"[..], &mut output)?;
+        assert_eq!(chosen, [commands[1].clone()]);
+
+        let output = String::from_utf8(output)?;
+        assert!(output.contains("UUID Generator"));
+        assert!(output.contains("--pick nanoid"));
+        assert!(output.contains("Invalid choice."));
+
+        assert!(choose(&zuk, commands, &b""[..], std::io::sink())?.is_empty());
+        Ok(())
+    }
+}
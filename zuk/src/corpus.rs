@@ -0,0 +1,84 @@
+use crate::chooser::skill_name;
+use anyhow::{bail, Result};
+use serde_derive::Serialize;
+use std::io::Write;
+use yozuk_sdk::prelude::*;
+
+pub fn dump_corpus<W>(skill: Option<&str>, mut writer: W) -> Result<()>
+where
+    W: Write,
+{
+    let key = if let Some(skill) = skill {
+        let entry = yozuk::SKILLS
+            .iter()
+            .find(|item| item.key == skill || skill_name(item.key) == skill);
+        if let Some(entry) = entry {
+            Some(entry.key)
+        } else {
+            bail!("Unknown skill: {}", skill);
+        }
+    } else {
+        None
+    };
+
+    let sentences = yozuk_model::dump_corpus(yozuk::SKILLS, &Environment::new(), key)?;
+    for sentence in &sentences {
+        let line = CorpusLine {
+            skill: &sentence.skill,
+            weight: sentence.weight,
+            tokens: sentence
+                .tokens
+                .iter()
+                .map(|token| CorpusToken {
+                    data: token.as_str(),
+                    tag: &token.tag,
+                })
+                .collect(),
+        };
+        serde_json::to_writer(&mut writer, &line)?;
+        writeln!(&mut writer)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct CorpusLine<'a> {
+    skill: &'a str,
+    weight: f64,
+    tokens: Vec<CorpusToken<'a>>,
+}
+
+#[derive(Serialize)]
+struct CorpusToken<'a> {
+    data: &'a str,
+    tag: &'a str,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dump_corpus() {
+        let mut output = Vec::<u8>::new();
+        dump_corpus(Some("dice"), &mut output).unwrap();
+
+        let lines = String::from_utf8(output).unwrap();
+        let lines = lines
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert!(!lines.is_empty());
+        assert!(lines.iter().all(|line| line["skill"] == "yozuk-skill-dice"));
+        assert!(lines.iter().any(|line| {
+            line["tokens"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|token| token["data"] == "dice" && token["tag"] == "command")
+        }));
+
+        assert!(dump_corpus(Some("no-such-skill"), &mut Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,114 @@
+use anyhow::Result;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use yozuk_sdk::prelude::*;
+
+pub fn write_data_blocks(dst: &Path, blocks: &[&block::Data]) -> Result<Vec<PathBuf>> {
+    if let [data] = blocks {
+        if !is_dir_path(dst) {
+            fs::write(dst, &data.data)?;
+            return Ok(vec![dst.to_path_buf()]);
+        }
+    }
+
+    if !blocks.is_empty() {
+        fs::create_dir_all(dst)?;
+    }
+
+    let mut names = HashSet::new();
+    let mut paths = Vec::with_capacity(blocks.len());
+    for (index, data) in blocks.iter().enumerate() {
+        let name = file_name(data, index);
+        let name = if names.contains(&name) {
+            format!("{}-{}", index + 1, name)
+        } else {
+            name
+        };
+        let path = dst.join(&name);
+        fs::write(&path, &data.data)?;
+        names.insert(name);
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+pub fn is_dir_path(path: &Path) -> bool {
+    path.is_dir()
+        || path
+            .as_os_str()
+            .to_string_lossy()
+            .ends_with(std::path::is_separator)
+}
+
+fn file_name(data: &block::Data, index: usize) -> String {
+    Path::new(&data.file_name)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| {
+            format!(
+                "{}.{}",
+                index + 1,
+                yozuk_helper_filetype::get_file_extension(&data.media_type)
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_name() {
+        let data = block::Data::new()
+            .set_data(vec![0x89, 0x50, 0x4e, 0x47])
+            .set_media_type(media_type!(IMAGE / PNG));
+        assert_eq!(file_name(&data, 0), "1.png");
+
+        let data = data.set_file_name("../qrcode.png");
+        assert_eq!(file_name(&data, 0), "qrcode.png");
+
+        let data = block::Data::new().set_text_data("hello");
+        assert_eq!(file_name(&data, 2), "3.txt");
+    }
+
+    #[test]
+    fn test_write_data_blocks() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let text = block::Data::new().set_text_data("hello");
+        let file = dir.path().join("hello.txt");
+        assert_eq!(write_data_blocks(&file, &[&text])?, [file.as_path()]);
+        assert_eq!(fs::read(&file)?, b"hello");
+
+        let image = block::Data::new()
+            .set_data(vec![0x89, 0x50, 0x4e, 0x47])
+            .set_media_type(media_type!(IMAGE / PNG))
+            .set_file_name("qrcode.png");
+        let out = dir.path().join("out");
+        let paths = write_data_blocks(&out, &[&text, &image, &image])?;
+        assert_eq!(
+            paths,
+            [
+                out.join("1.txt"),
+                out.join("qrcode.png"),
+                out.join("3-qrcode.png")
+            ]
+        );
+        assert_eq!(fs::read(out.join("qrcode.png"))?, image.data);
+
+        assert_eq!(
+            write_data_blocks(dir.path(), &[&text])?,
+            [dir.path().join("1.txt")]
+        );
+
+        let missing = dir.path().join("missing/");
+        assert_eq!(
+            write_data_blocks(&missing, &[&text])?,
+            [missing.join("1.txt")]
+        );
+        assert_eq!(fs::read(missing.join("1.txt"))?, b"hello");
+        Ok(())
+    }
+}
@@ -0,0 +1,450 @@
+#![cfg(all(feature = "http-server", not(target_arch = "wasm32")))]
+
+use crate::rpc::{
+    CompletionsRequest, CompletionsResponse, GetCommandsRequest, GetCommandsResponse,
+    RunCommandsRequest, RunCommandsResponse, SuggestionsRequest, SuggestionsResponse,
+};
+use anyhow::{anyhow, bail, Result};
+use mediatype::{media_type, MediaTypeBuf};
+use multipart::server::Multipart;
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use std::io::{Cursor, Read};
+use std::net::ToSocketAddrs;
+use std::thread;
+use tiny_http::{Header, Method, Request, Response, Server, StatusCode};
+use yozuk::Yozuk;
+use yozuk_sdk::prelude::*;
+
+const REQUEST_FIELD: &str = "request";
+const MAX_BODY_LENGTH: usize = 16 * 1024 * 1024;
+const DEFAULT_WORKERS: usize = 4;
+
+pub fn start_server<A: ToSocketAddrs>(zuk: Yozuk, addr: A) -> Result<()> {
+    let server = Server::http(addr).map_err(|err| anyhow!(err))?;
+    eprintln!("Listening on http://{}", server.server_addr());
+    serve(&zuk, &server);
+    Ok(())
+}
+
+fn serve(zuk: &Yozuk, server: &Server) {
+    let workers = thread::available_parallelism().map_or(DEFAULT_WORKERS, usize::from);
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                for mut request in server.incoming_requests() {
+                    let response = match handle(zuk, &mut request) {
+                        Ok(value) => json_response(200, &value),
+                        Err(err) => json_response(err.status, &json!({ "error": err.message })),
+                    };
+                    let _ = request.respond(response);
+                }
+            });
+        }
+    });
+}
+
+fn handle(zuk: &Yozuk, request: &mut Request) -> Result<serde_json::Value, Error> {
+    let path = request
+        .url()
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    match (request.method(), path.as_str()) {
+        (Method::Get, "/schema") => Ok(schema()),
+        (Method::Post, "/commands") => {
+            let (req, streams) = read_body::<GetCommandsRequest>(request)?;
+            let tokens: Vec<Token> = req.into();
+            let commands = zuk.get_commands(&tokens, &streams);
+            Ok(to_value(GetCommandsResponse { commands }))
+        }
+        (Method::Post, "/run") => {
+            let (req, mut streams) = read_body::<RunCommandsRequest>(request)?;
+            let result: RunCommandsResponse = zuk
+                .run_commands(req.commands, &mut streams, req.user.as_ref())
+                .into();
+            Ok(to_value(result))
+        }
+        (Method::Post, "/suggestions") => {
+            let (req, streams) = read_body::<SuggestionsRequest>(request)?;
+            let amount = req.amount;
+            let tokens: Vec<Token> = GetCommandsRequest::from(req).into();
+            let suggestions = if tokens.is_empty() && streams.is_empty() {
+                zuk.random_suggestions(amount)
+            } else {
+                zuk.suggestions(&tokens, &streams, amount)
+            };
+            Ok(to_value(SuggestionsResponse { suggestions }))
+        }
+        (Method::Post, "/completions") => {
+            let (req, _) = read_body::<CompletionsRequest>(request)?;
+            let pos = req.pos.unwrap_or(req.input.len());
+            let (start, candidates) = zuk.completions(&req.input, pos, req.amount);
+            Ok(to_value(CompletionsResponse { start, candidates }))
+        }
+        (_, "/schema" | "/commands" | "/run" | "/suggestions" | "/completions") => {
+            Err(Error::new(405, "Method not allowed"))
+        }
+        _ => Err(Error::new(404, "Not found")),
+    }
+}
+
+fn read_body<T>(request: &mut Request) -> Result<(T, Vec<InputStream>), Error>
+where
+    T: DeserializeOwned + Default,
+{
+    let content_type = request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv("Content-Type"))
+        .map(|header| header.value.to_string())
+        .unwrap_or_default();
+
+    let body = read_to_end(request)?;
+    if let Some(boundary) = multipart_boundary(&content_type) {
+        read_multipart(Cursor::new(body), boundary).map_err(|err| Error::new(400, err))
+    } else {
+        let req = if body.iter().all(u8::is_ascii_whitespace) {
+            T::default()
+        } else {
+            serde_json::from_slice(&body).map_err(|err| Error::new(400, err))?
+        };
+        Ok((req, vec![]))
+    }
+}
+
+fn read_to_end(request: &mut Request) -> Result<Vec<u8>, Error> {
+    let too_large = || Error::new(413, "Request body too large");
+    if request.body_length().unwrap_or(0) > MAX_BODY_LENGTH {
+        return Err(too_large());
+    }
+    let mut body = Vec::new();
+    request
+        .as_reader()
+        .take(MAX_BODY_LENGTH as u64 + 1)
+        .read_to_end(&mut body)
+        .map_err(|err| Error::new(400, err))?;
+    if body.len() > MAX_BODY_LENGTH {
+        return Err(too_large());
+    }
+    Ok(body)
+}
+
+fn read_multipart<T, R>(reader: R, boundary: &str) -> Result<(T, Vec<InputStream>)>
+where
+    T: DeserializeOwned + Default,
+    R: Read,
+{
+    let mut multipart = Multipart::with_body(reader, boundary);
+    let mut req = None;
+    let mut streams = Vec::new();
+    while let Some(mut field) = multipart.read_entry()? {
+        let mut data = Vec::new();
+        field.data.read_to_end(&mut data)?;
+        if &*field.headers.name == REQUEST_FIELD && field.headers.filename.is_none() {
+            if req.is_some() {
+                bail!("Duplicate {:?} field", REQUEST_FIELD);
+            }
+            req = Some(serde_json::from_slice(&data)?);
+        } else {
+            let media_type = field
+                .headers
+                .content_type
+                .and_then(|mime| MediaTypeBuf::from_string(mime.to_string()).ok())
+                .unwrap_or_else(|| media_type!(APPLICATION / OCTET_STREAM).into());
+            let stream = InputStream::new(Cursor::new(data), media_type);
+            let mut stream = match field.headers.filename {
+                Some(name) => stream.set_file_name(name),
+                None => stream,
+            };
+            stream.read_header()?;
+            streams.push(stream);
+        }
+    }
+    Ok((req.unwrap_or_default(), streams))
+}
+
+fn multipart_boundary(content_type: &str) -> Option<&str> {
+    let mut params = content_type.split(';').map(str::trim);
+    if !params.next()?.eq_ignore_ascii_case("multipart/form-data") {
+        return None;
+    }
+    params
+        .filter_map(|param| param.split_once('='))
+        .find(|(key, _)| key.eq_ignore_ascii_case("boundary"))
+        .map(|(_, value)| value.trim_matches('"'))
+}
+
+fn json_response(status: u16, value: &serde_json::Value) -> Response<Cursor<Vec<u8>>> {
+    Response::from_data(serde_json::to_vec(value).unwrap())
+        .with_status_code(StatusCode(status))
+        .with_header(Header::from_bytes("Content-Type", "application/json").unwrap())
+}
+
+fn to_value<T: serde::Serialize>(value: T) -> serde_json::Value {
+    serde_json::to_value(value).unwrap()
+}
+
+fn schema() -> serde_json::Value {
+    json!({
+        "endpoints": [
+            {
+                "method": "GET",
+                "path": "/schema",
+                "description": "Describe the available endpoints",
+            },
+            {
+                "method": "POST",
+                "path": "/commands",
+                "description": "Get commands matching the input",
+                "request": { "input": "string", "input_tokens": "[Token]?" },
+                "response": { "commands": "[CommandArgs]" },
+            },
+            {
+                "method": "POST",
+                "path": "/run",
+                "description": "Run commands and return their outputs",
+                "request": { "commands": "[CommandArgs]", "user": "UserContext?" },
+                "response": { "result": "\"ok\" | \"error\"", "outputs": "[Output]" },
+            },
+            {
+                "method": "POST",
+                "path": "/suggestions",
+                "description": "Suggest queries for the input, or random ones if it is empty",
+                "request": { "input": "string?", "amount": "number?" },
+                "response": { "suggestions": "[string]" },
+            },
+            {
+                "method": "POST",
+                "path": "/completions",
+                "description": "Complete the word at the cursor position",
+                "request": { "input": "string", "pos": "number?", "amount": "number?" },
+                "response": { "start": "number", "candidates": "[string]" },
+            },
+        ],
+        "multipart": {
+            "content_type": "multipart/form-data",
+            "description": format!(
+                "POST endpoints also accept multipart bodies. The {:?} field holds the JSON request and every other field is passed to the skills as an input stream.",
+                REQUEST_FIELD
+            ),
+        },
+    })
+}
+
+struct Error {
+    status: u16,
+    message: String,
+}
+
+impl Error {
+    fn new<T: ToString>(status: u16, message: T) -> Self {
+        Self {
+            status,
+            message: message.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::{SocketAddr, TcpStream};
+
+    fn start() -> SocketAddr {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let addr = server.server_addr().to_ip().unwrap();
+        thread::spawn(move || serve(&Yozuk::builder().build(), &server));
+        addr
+    }
+
+    fn send(
+        addr: SocketAddr,
+        method: &str,
+        path: &str,
+        content_type: &str,
+        body: &[u8],
+    ) -> (u16, serde_json::Value) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nContent-Type: {}\r\nContent-Length: {}\r\n\r\n",
+            method,
+            path,
+            content_type,
+            body.len()
+        )
+        .unwrap();
+        stream.write_all(body).unwrap();
+
+        let mut reader = BufReader::new(stream);
+        let mut status = String::new();
+        reader.read_line(&mut status).unwrap();
+        let status = status.split(' ').nth(1).unwrap().parse().unwrap();
+        let mut line = String::new();
+        while reader.read_line(&mut line).unwrap() > 2 {
+            line.clear();
+        }
+        let mut body = Vec::new();
+        reader.read_to_end(&mut body).unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[test]
+    fn test_multipart_boundary() {
+        assert_eq!(
+            multipart_boundary("multipart/form-data; boundary=\"abc\""),
+            Some("abc")
+        );
+        assert_eq!(
+            multipart_boundary("Multipart/Form-Data;charset=utf-8;Boundary=xyz"),
+            Some("xyz")
+        );
+        assert_eq!(multipart_boundary("application/json"), None);
+        assert_eq!(multipart_boundary("multipart/form-data"), None);
+    }
+
+    #[test]
+    fn test_http() {
+        let addr = start();
+
+        let (status, res) = send(
+            addr,
+            "POST",
+            "/commands",
+            "application/json",
+            br#"{"input":"1 + 1"}"#,
+        );
+        assert_eq!(status, 200);
+        assert_eq!(
+            res,
+            to_value(GetCommandsResponse {
+                commands: vec![
+                    CommandArgs::new().add_args(["yozuk-skill-calc", "1+1"]),
+                    CommandArgs::new().add_args(["yozuk-skill-unicode", "1", "+", "1"]),
+                ],
+            })
+        );
+
+        let req = RunCommandsRequest {
+            commands: vec![CommandArgs::new().add_args(["yozuk-skill-calc", "1+1"])],
+            ..Default::default()
+        };
+        let (status, res) = send(
+            addr,
+            "POST",
+            "/run",
+            "application/json",
+            &serde_json::to_vec(&req).unwrap(),
+        );
+        assert_eq!(status, 200);
+        assert_eq!(
+            res,
+            to_value(RunCommandsResponse::Ok(vec![Output::new()
+                .set_title("Calculator")
+                .add_block(block::Data::new().set_text_data("2"))
+                .add_metadata(
+                    Metadata::docs("https://docs.yozuk.com/docs/skills/calc/").unwrap()
+                )]))
+        );
+
+        let (status, res) = send(addr, "POST", "/suggestions", "", br#"{"amount":3}"#);
+        assert_eq!(status, 200);
+        assert_eq!(res["suggestions"].as_array().unwrap().len(), 3);
+
+        let (status, res) = send(addr, "GET", "/schema", "", b"");
+        assert_eq!(status, 200);
+        assert!(res["endpoints"].as_array().unwrap().len() >= 4);
+
+        let (status, _) = send(addr, "GET", "/run", "", b"");
+        assert_eq!(status, 405);
+        let (status, _) = send(addr, "GET", "/", "", b"");
+        assert_eq!(status, 404);
+        let (status, res) = send(addr, "POST", "/run", "application/json", b"{");
+        assert_eq!(status, 400);
+        assert!(res["error"].is_string());
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "POST /commands HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY_LENGTH + 1
+        )
+        .unwrap();
+        let mut status = String::new();
+        BufReader::new(stream).read_line(&mut status).unwrap();
+        assert!(status.starts_with("HTTP/1.1 413 "));
+    }
+
+    #[test]
+    fn test_multipart() {
+        let addr = start();
+        let req = RunCommandsRequest {
+            commands: vec![CommandArgs::new().add_args([
+                "yozuk-skill-digest",
+                "--algorithm",
+                "sha1",
+            ])],
+            ..Default::default()
+        };
+        let req = serde_json::to_string(&req).unwrap();
+        let body = [
+            "--XYZ",
+            "Content-Disposition: form-data; name=\"request\"",
+            "",
+            &req,
+            "--XYZ",
+            "Content-Disposition: form-data; name=\"file\"; filename=\"hello.txt\"",
+            "Content-Type: text/plain",
+            "",
+            "Hello world",
+            "--XYZ--",
+            "",
+        ]
+        .join("\r\n");
+
+        let (status, res) = send(
+            addr,
+            "POST",
+            "/run",
+            "multipart/form-data; boundary=XYZ",
+            body.as_bytes(),
+        );
+        assert_eq!(status, 200);
+        assert_eq!(
+            res,
+            to_value(RunCommandsResponse::Ok(vec![Output::new()
+                .set_title("Digest")
+                .add_block(
+                    block::Data::new().set_text_data("7b502c3a1f48c8609ae212cdfb639dee39673f5e")
+                )
+                .add_metadata(
+                    Metadata::docs("https://docs.yozuk.com/docs/skills/digest/").unwrap()
+                )]))
+        );
+
+        let body = [
+            "--XYZ",
+            "Content-Disposition: form-data; name=\"file\"; filename=\"hello.txt\"",
+            "",
+            "SGVsbG8gd29ybGQ=",
+            "--XYZ--",
+            "",
+        ]
+        .join("\r\n");
+        let (status, res) = send(
+            addr,
+            "POST",
+            "/commands",
+            "multipart/form-data; boundary=XYZ",
+            body.as_bytes(),
+        );
+        assert_eq!(status, 200);
+        assert_eq!(
+            res["commands"][0]["args"],
+            json!(["yozuk-skill-base64", "--mode", "decode"])
+        );
+    }
+}
@@ -0,0 +1,209 @@
+use owo_colors::OwoColorize;
+use serde::de::{Deserialize, Deserializer, Error, MapAccess, SeqAccess, Visitor};
+use serde_json::value::RawValue;
+use std::fmt;
+use std::io::{self, Write};
+
+const FOLD_THRESHOLD: usize = 20;
+const FOLD_HEAD: usize = 10;
+const INDENT: &str = "  ";
+
+#[derive(Debug, PartialEq)]
+pub enum Node {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Node>),
+    Object(Vec<(String, Node)>),
+}
+
+impl Node {
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        serde_json::from_slice(data).ok()
+    }
+
+    pub fn render<W: Write>(&self, mut output: W) -> io::Result<()> {
+        self.render_indented(&mut output, 0)?;
+        writeln!(output)
+    }
+
+    fn render_indented<W: Write>(&self, output: &mut W, depth: usize) -> io::Result<()> {
+        match self {
+            Self::Null => write!(output, "{}", "null".magenta()),
+            Self::Bool(value) => write!(output, "{}", value.yellow()),
+            Self::Number(value) => write!(output, "{}", value.cyan()),
+            Self::String(value) => write!(output, "{}", quote(value).green()),
+            Self::Array(items) if items.is_empty() => write!(output, "[]"),
+            Self::Object(entries) if entries.is_empty() => write!(output, "{{}}"),
+            Self::Array(items) => {
+                let shown = if items.len() > FOLD_THRESHOLD {
+                    FOLD_HEAD
+                } else {
+                    items.len()
+                };
+                writeln!(output, "[")?;
+                for (i, item) in items[..shown].iter().enumerate() {
+                    write!(output, "{}", INDENT.repeat(depth + 1))?;
+                    item.render_indented(output, depth + 1)?;
+                    if i + 1 < items.len() {
+                        write!(output, ",")?;
+                    }
+                    writeln!(output)?;
+                }
+                if shown < items.len() {
+                    let folded = format!("… {} more items", items.len() - shown);
+                    writeln!(output, "{}{}", INDENT.repeat(depth + 1), folded.dimmed())?;
+                }
+                write!(output, "{}]", INDENT.repeat(depth))
+            }
+            Self::Object(entries) => {
+                writeln!(output, "{{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    write!(
+                        output,
+                        "{}{}: ",
+                        INDENT.repeat(depth + 1),
+                        quote(key).blue().bold()
+                    )?;
+                    value.render_indented(output, depth + 1)?;
+                    if i + 1 < entries.len() {
+                        write!(output, ",")?;
+                    }
+                    writeln!(output)?;
+                }
+                write!(output, "{}}}", INDENT.repeat(depth))
+            }
+        }
+    }
+}
+
+fn quote(s: &str) -> String {
+    serde_json::to_string(s).unwrap()
+}
+
+impl<'de> Deserialize<'de> for Node {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // Numbers are kept as written so that they do not lose precision.
+        let raw = <&RawValue>::deserialize(deserializer)?;
+        let text = raw.get();
+        if text.starts_with(|c: char| c == '-' || c.is_ascii_digit()) {
+            return Ok(Node::Number(text.to_string()));
+        }
+        serde_json::Deserializer::from_str(text)
+            .deserialize_any(NodeVisitor)
+            .map_err(D::Error::custom)
+    }
+}
+
+struct NodeVisitor;
+
+impl<'de> Visitor<'de> for NodeVisitor {
+    type Value = Node;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON value")
+    }
+
+    fn visit_unit<E>(self) -> Result<Node, E> {
+        Ok(Node::Null)
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Node, E> {
+        Ok(Node::Bool(value))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Node, E> {
+        Ok(Node::String(value.to_string()))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Node, E> {
+        Ok(Node::String(value))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Node, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(Node::Array(items))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Node, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut entries = Vec::new();
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(Node::Object(entries))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strip(data: Vec<u8>) -> String {
+        let data = String::from_utf8(data).unwrap();
+        let mut plain = String::new();
+        let mut chars = data.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|&c| c == 'm');
+            } else {
+                plain.push(c);
+            }
+        }
+        plain
+    }
+
+    #[test]
+    fn test_render() {
+        let node = Node::parse(br#"{"z":1,"a":[true,null,"x"],"e":{}}"#).unwrap();
+        let mut output = Vec::new();
+        node.render(&mut output).unwrap();
+        assert_eq!(
+            strip(output),
+            "{\n  \"z\": 1,\n  \"a\": [\n    true,\n    null,\n    \"x\"\n  ],\n  \"e\": {}\n}\n"
+        );
+        assert!(Node::parse(b"{").is_none());
+    }
+
+    #[test]
+    fn test_number_precision() {
+        let node = Node::parse(br#"[12345678901234567890123, 0.1000000000000000055511, -1e400]"#);
+        assert_eq!(
+            node,
+            Some(Node::Array(vec![
+                Node::Number("12345678901234567890123".into()),
+                Node::Number("0.1000000000000000055511".into()),
+                Node::Number("-1e400".into()),
+            ]))
+        );
+        assert_eq!(
+            Node::parse(b"{ \"a\\n\" : 1.50 }\n"),
+            Some(Node::Object(vec![(
+                "a\n".into(),
+                Node::Number("1.50".into())
+            )]))
+        );
+    }
+
+    #[test]
+    fn test_fold() {
+        let data = serde_json::to_vec(&(0..100).collect::<Vec<_>>()).unwrap();
+        let mut output = Vec::new();
+        Node::parse(&data).unwrap().render(&mut output).unwrap();
+        let output = strip(output);
+        assert!(output.contains("  9,\n  … 90 more items\n]"));
+        assert!(!output.contains("10,"));
+    }
+}
@@ -3,55 +3,123 @@
 
 use anyhow::Result;
 use clap::Parser;
-use std::fs::File;
-use std::io;
+use owo_colors::OwoColorize;
+use std::fs::{self, File};
+use std::io::{self, BufReader};
 use yozuk::Yozuk;
 use yozuk_sdk::prelude::*;
 
 mod args;
+mod batch;
+mod chooser;
+#[cfg(all(debug_assertions, feature = "dump-corpus"))]
+mod corpus;
+mod files;
+mod http;
+mod json;
+mod pager;
 mod printer;
 mod repl;
 mod rpc;
+mod sandbox;
+mod session;
+mod sixel;
 mod term;
 
 use args::*;
 use printer::*;
 
 fn main() -> Result<()> {
-    enter_secure_context()?;
-
     let args = Args::parse();
-    let app = App::new(args)?;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    let history = is_repl(&args).then(repl::open_history).flatten();
+    #[cfg(target_arch = "wasm32")]
+    let history = None;
+
+    // The socket and its cleanup handler are set up before the sandbox forbids them.
+    #[cfg(all(feature = "rpc", not(target_arch = "wasm32")))]
+    let listener = args
+        .rpc_listen
+        .as_ref()
+        .map(rpc::Endpoint::bind)
+        .transpose()?;
+
+    restrict_filesystem(&args)?;
+    enter_secure_context(&args, history.as_ref())?;
+
+    let app = App::new(args, history)?;
+
+    #[cfg(all(feature = "rpc", not(target_arch = "wasm32")))]
+    if let Some(listener) = listener {
+        return listener.serve(app.zuk);
+    }
+
     app.run()
 }
 
 struct App {
     args: Args,
     zuk: Yozuk,
+    interactive: bool,
+    history: Option<File>,
 }
 
 impl App {
-    fn new(args: Args) -> Result<Self> {
-        let zuk = Yozuk::builder()
+    fn new(args: Args, history: Option<File>) -> Result<Self> {
+        let mut builder = Yozuk::builder();
+        if let Some(path) = &args.model {
+            match fs::read(path) {
+                Ok(data) => match yozuk::load_model(data) {
+                    Some(model) => builder = builder.set_model(model),
+                    None => eprintln!(
+                        "{}",
+                        "The model file is not compatible with this build. Using the embedded model."
+                            .yellow()
+                    ),
+                },
+                Err(err) => eprintln!(
+                    "{}",
+                    format!("Failed to read the model file: {}. Using the embedded model.", err)
+                        .yellow()
+                ),
+            }
+        }
+        let zuk = builder
             .add_redirection(tk!(["exit"]), vec!["exit"])
             .add_redirection(tk!(["bye"]), vec!["exit"])
-            .set_user_context(UserContext {
-                #[cfg(not(target_os = "wasi"))]
-                username: std::iter::once(whoami::username()).find(|name| name != "anonymous"),
-                ..Default::default()
-            })
+            .set_user_context(user_context())
             .build();
-        Ok(Self { args, zuk })
+        Ok(Self {
+            args,
+            zuk,
+            interactive: false,
+            history,
+        })
     }
 
     fn run(mut self) -> Result<()> {
         #[cfg(all(feature = "rpc", not(target_arch = "wasm32")))]
         if self.args.rpc {
             let stdin = io::stdin();
-            let stdout = io::stdout();
             let stdin = stdin.lock();
+            return rpc::start_server(self.zuk, stdin, io::stdout());
+        }
+
+        #[cfg(all(feature = "http-server", not(target_arch = "wasm32")))]
+        if let Some(addr) = &self.args.serve {
+            return http::start_server(self.zuk, addr.as_str());
+        }
+
+        if let Some(path) = &self.args.batch {
+            let stdout = io::stdout();
             let stdout = stdout.lock();
-            return rpc::start_server(self.zuk, stdin, stdout);
+            return if path.as_os_str() == "-" {
+                batch::run_batch(&self.zuk, io::stdin().lock(), stdout)
+            } else {
+                let reader = BufReader::new(File::open(path)?);
+                batch::run_batch(&self.zuk, reader, stdout)
+            };
         }
 
         #[cfg(debug_assertions)]
@@ -62,6 +130,12 @@ impl App {
             return Ok(());
         }
 
+        #[cfg(all(debug_assertions, feature = "dump-corpus"))]
+        if let Some(skill) = &self.args.dump_corpus {
+            let stdout = io::stdout();
+            return corpus::dump_corpus(skill.as_deref(), stdout.lock());
+        }
+
         let mut streams = vec![];
         if !term::is_stdin_tty() {
             streams.push(InputStream::new(
@@ -88,6 +162,9 @@ impl App {
             let suggestions = if tokens.is_empty() && streams.is_empty() {
                 self.zuk.random_suggestions(self.args.suggestions as _)
             } else {
+                for stream in streams.iter_mut() {
+                    stream.read_header()?;
+                }
                 self.zuk
                     .suggestions(&tokens, &streams, self.args.suggestions as _)
             };
@@ -100,16 +177,44 @@ impl App {
         let repl = streams.is_empty() && self.args.query.is_empty();
         if repl {
             self.args.verbose += 1;
+            self.interactive = true;
 
             #[cfg(not(target_arch = "wasm32"))]
             {
                 println!("Hi. I'm Yozuk. How may I assist you?");
 
-                let mut repl = repl::Repl::new();
+                let printer = TerminalPrinter::new(&self.args);
+                let mut repl = repl::Repl::new(&self.zuk, self.history.take());
+                let mut session = session::Session::new(user_context());
                 while let Some(line) = repl.readline() {
+                    if let Some(command) = session::MetaCommand::parse(&line) {
+                        match command.and_then(|command| session.exec(command, io::stdout())) {
+                            Ok(true) => continue,
+                            Ok(false) => break,
+                            Err(err) => {
+                                printer.print_error_str(&err.to_string())?;
+                                continue;
+                            }
+                        }
+                    }
+
                     let tokens = Tokenizer::new().tokenize(&line);
-                    if !tokens.is_empty() && !self.exec_command(&tokens, &mut [])? {
-                        break;
+                    if tokens.is_empty() {
+                        continue;
+                    }
+                    let (tokens, mut streams) = match session.prepare(&line, tokens) {
+                        Ok(prepared) => prepared,
+                        Err(err) => {
+                            printer.print_error_str(&err.to_string())?;
+                            continue;
+                        }
+                    };
+                    match self.exec_command(&tokens, &mut streams, Some(&session.user))? {
+                        Some(outputs) => {
+                            let numbers = session.push_results(&outputs);
+                            printer.print_result_numbers(&numbers)?;
+                        }
+                        None => break,
                     }
                 }
 
@@ -118,15 +223,20 @@ impl App {
 
             #[cfg(target_arch = "wasm32")]
             {
-                self.exec_command(&[], &mut [])?;
+                self.exec_command(&[], &mut [], None)?;
             }
         } else {
-            self.exec_command(&tokens, &mut streams)?;
+            self.exec_command(&tokens, &mut streams, None)?;
         }
         Ok(())
     }
 
-    fn exec_command(&self, tokens: &[Token], streams: &mut [InputStream]) -> Result<bool> {
+    fn exec_command(
+        &self,
+        tokens: &[Token],
+        streams: &mut [InputStream],
+        user: Option<&UserContext>,
+    ) -> Result<Option<Vec<Output>>> {
         for stream in streams.iter_mut() {
             stream.read_header()?;
         }
@@ -142,11 +252,36 @@ impl App {
         if let [cmd] = &commands[..] {
             if let [name, kind] = &cmd.args[..] {
                 if name == "yozuk-redirect" && kind == "exit" {
-                    return Ok(false);
+                    return Ok(None);
                 }
             }
         }
 
+        let commands = if let Some(skill) = &self.args.pick {
+            let picked = chooser::pick(&commands, skill);
+            if picked.is_empty() && !commands.is_empty() {
+                let candidates = commands
+                    .iter()
+                    .map(|cmd| chooser::skill_name(&cmd.args[0]))
+                    .collect::<Vec<_>>();
+                printer.print_error_str(&format!(
+                    "Skill {} does not match your request. Candidates: {}",
+                    skill,
+                    candidates.join(", ")
+                ))?;
+                return Ok(Some(vec![]));
+            }
+            picked
+        } else if self.interactive && commands.len() > 1 && !self.args.run {
+            let commands = chooser::choose(&self.zuk, commands, io::stdin().lock(), io::stderr())?;
+            if commands.is_empty() {
+                return Ok(Some(vec![]));
+            }
+            commands
+        } else {
+            commands
+        };
+
         if commands.is_empty() {
             printer.print_error_str("Sorry, I can't understand your request.")?;
             if let [suggestion, ..] = &self.zuk.suggestions(tokens, streams, 1)[..] {
@@ -155,19 +290,15 @@ impl App {
         } else {
             if self.args.dry_run {
                 printer.print_commands(&commands)?;
-                return Ok(true);
+                return Ok(Some(vec![]));
             }
 
-            let result = self.zuk.run_commands(commands, streams, None);
+            let result = self.zuk.run_commands(commands, streams, user);
 
             match result {
                 Ok(outputs) => {
-                    for i in 0..outputs.len() {
-                        printer.print_result(&outputs[i])?;
-                        if outputs.len() > 1 && i < outputs.len() - 1 {
-                            println!("--");
-                        }
-                    }
+                    printer.print_outputs(&outputs)?;
+                    return Ok(Some(outputs));
                 }
                 Err(errors) => {
                     printer.print_error(&errors)?;
@@ -175,26 +306,121 @@ impl App {
             }
         }
 
-        Ok(true)
+        Ok(Some(vec![]))
+    }
+}
+
+fn user_context() -> UserContext {
+    UserContext {
+        #[cfg(not(target_os = "wasi"))]
+        username: std::iter::once(whoami::username()).find(|name| name != "anonymous"),
+        ..Default::default()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn is_listening(args: &Args) -> bool {
+    let mut listening = false;
+    #[cfg(feature = "http-server")]
+    {
+        listening |= args.serve.is_some();
+    }
+    #[cfg(feature = "rpc")]
+    {
+        listening |= args.rpc_listen.is_some();
+    }
+    listening
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn is_repl(args: &Args) -> bool {
+    args.query.is_empty()
+        && args.input.is_empty()
+        && args.batch.is_none()
+        && !is_listening(args)
+        && term::is_stdin_tty()
+}
+
+#[cfg(all(target_os = "linux", feature = "landlock"))]
+fn filesystem_policy(args: &Args) -> Option<sandbox::Policy> {
+    let mut server = is_listening(args);
+    #[cfg(feature = "rpc")]
+    {
+        server |= args.rpc;
+    }
+    // The REPL loads and saves files at arbitrary paths with `:load` and `:save`.
+    if server || is_repl(args) {
+        None
+    } else {
+        Some(sandbox::Policy::from_args(args))
     }
 }
 
+// Landlock must be applied before the seccomp filter, which blocks its syscalls.
+#[cfg(all(target_os = "linux", feature = "landlock"))]
+fn restrict_filesystem(args: &Args) -> Result<()> {
+    use landlock::RulesetStatus;
+
+    let policy = match filesystem_policy(args) {
+        Some(policy) => policy,
+        None => return Ok(()),
+    };
+    let status = policy.restrict_self()?;
+    if status != RulesetStatus::FullyEnforced && args.verbose > 0 {
+        eprintln!(
+            "{}",
+            "Landlock is not fully supported by this kernel; filesystem access is not restricted."
+                .yellow()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(not(all(target_os = "linux", feature = "landlock")))]
+fn restrict_filesystem(_args: &Args) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(all(
+    target_os = "linux",
+    target_arch = "x86_64",
+    feature = "secure-context"
+))]
+fn enter_secure_context(args: &Args, history: Option<&File>) -> Result<()> {
+    secure_context(args, history)?.apply_to_all_threads()?;
+    Ok(())
+}
+
 #[cfg(all(
     target_os = "linux",
     target_arch = "x86_64",
     feature = "secure-context"
 ))]
-fn enter_secure_context() -> Result<()> {
-    use extrasafe::builtins::{danger_zone::Threads, SystemIO};
+fn secure_context(args: &Args, history: Option<&File>) -> Result<extrasafe::SafetyContext> {
+    use extrasafe::builtins::{danger_zone::Threads, Networking, SystemIO};
     use extrasafe::{Rule, RuleSet, SafetyContext};
     use std::collections::HashMap;
     use syscalls::Sysno;
 
-    struct CustomRules;
+    struct CustomRules {
+        write_files: bool,
+        repl: bool,
+        serve: bool,
+    }
 
     impl RuleSet for CustomRules {
         fn simple_rules(&self) -> Vec<Sysno> {
-            vec![Sysno::poll, Sysno::ppoll]
+            let mut rules = vec![Sysno::poll, Sysno::ppoll];
+            if self.write_files {
+                rules.extend([Sysno::mkdir, Sysno::mkdirat]);
+            }
+            if self.repl {
+                rules.push(Sysno::ftruncate);
+            }
+            if self.serve {
+                rules.extend([Sysno::shutdown, Sysno::unlink, Sysno::unlinkat]);
+            }
+            rules
         }
 
         fn conditional_rules(&self) -> HashMap<Sysno, Vec<Rule>> {
@@ -206,20 +432,43 @@ fn enter_secure_context() -> Result<()> {
         }
     }
 
-    SafetyContext::new()
+    let serve = is_listening(args);
+    let repl = is_repl(args) || history.is_some();
+    // `:save` writes files from the REPL.
+    let write_files = args.output.is_some() || repl;
+    let networking = if serve {
+        Networking::nothing()
+            .allow_start_tcp_servers()
+            .yes_really()
+            .allow_running_tcp_servers()
+    } else {
+        Networking::nothing()
+    };
+    let io = if write_files {
+        SystemIO::nothing()
+            .allow_open()
+            .yes_really()
+            .allow_write()
+            .allow_metadata()
+    } else if serve {
+        // Networking already allows unconditional writes.
+        SystemIO::nothing().allow_open_readonly()
+    } else {
+        SystemIO::nothing()
+            .allow_open_readonly()
+            .allow_stdout()
+            .allow_stderr()
+    };
+
+    Ok(SafetyContext::new()
         .enable(Threads::nothing().allow_create())?
-        .enable(
-            SystemIO::nothing()
-                .allow_open_readonly()
-                .allow_close()
-                .allow_read()
-                .allow_stdout()
-                .allow_stderr()
-                .allow_ioctl(),
-        )?
-        .enable(CustomRules)?
-        .apply_to_all_threads()?;
-    Ok(())
+        .enable(io.allow_close().allow_read().allow_ioctl())?
+        .enable(networking)?
+        .enable(CustomRules {
+            write_files,
+            repl,
+            serve,
+        })?)
 }
 
 #[cfg(not(all(
@@ -227,6 +476,109 @@ fn enter_secure_context() -> Result<()> {
     target_arch = "x86_64",
     feature = "secure-context"
 )))]
-fn enter_secure_context() -> Result<()> {
+fn enter_secure_context(_args: &Args, _history: Option<&File>) -> Result<()> {
     Ok(())
 }
+
+#[cfg(all(
+    test,
+    target_os = "linux",
+    target_arch = "x86_64",
+    feature = "secure-context"
+))]
+mod tests {
+    use super::*;
+    use landlock::RulesetStatus;
+    use std::thread;
+
+    #[test]
+    fn test_landlock_under_seccomp() -> Result<()> {
+        let supported = thread::spawn(|| sandbox::Policy::new().restrict_self())
+            .join()
+            .unwrap()?
+            == RulesetStatus::FullyEnforced;
+        if !supported {
+            return Ok(());
+        }
+
+        let dir = tempfile::tempdir()?;
+        let input = dir.path().join("input.txt");
+        let forbidden = dir.path().join("forbidden.txt");
+        fs::write(&input, "input")?;
+        fs::write(&forbidden, "forbidden")?;
+
+        let args = Args::parse_from(["zuk", "-i", input.to_str().unwrap(), "md5"]);
+
+        // Both layers apply to the calling thread only.
+        thread::spawn(move || -> Result<()> {
+            let status = filesystem_policy(&args).unwrap().restrict_self()?;
+            secure_context(&args, None)?.apply_to_current_thread()?;
+            assert_eq!(status, RulesetStatus::FullyEnforced);
+            assert_eq!(fs::read_to_string(&input)?, "input");
+            assert!(fs::read(&forbidden).is_err());
+            Ok(())
+        })
+        .join()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_save_under_seccomp() -> Result<()> {
+        use session::{MetaCommand, Session};
+
+        let dir = tempfile::tempdir()?;
+        let file = dir.path().join("result.txt");
+        let subdir = dir.path().join("results/");
+        let history = tempfile::tempfile()?;
+        let args = Args::parse_from(["zuk"]);
+
+        thread::spawn(move || -> Result<()> {
+            secure_context(&args, Some(&history))?.apply_to_current_thread()?;
+            let mut session = Session::new(UserContext::default());
+            session.push_results(&[
+                Output::new().add_block(block::Data::new().set_text_data("hello"))
+            ]);
+            session.exec(MetaCommand::Save(1, file.clone()), io::sink())?;
+            session.exec(MetaCommand::Save(1, subdir.clone()), io::sink())?;
+            assert_eq!(fs::read_to_string(&file)?, "hello");
+            assert_eq!(fs::read_dir(&subdir)?.count(), 1);
+            Ok(())
+        })
+        .join()
+        .unwrap()
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_rpc_listen_under_seccomp() -> Result<()> {
+        use std::io::{BufRead, Write};
+        use std::os::unix::fs::PermissionsExt;
+        use std::os::unix::net::UnixStream;
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("zuk.sock");
+        let endpoint = format!("unix:{}", path.display());
+        let args = Args::parse_from(["zuk", "--rpc-listen", endpoint.as_str()]);
+
+        // Same order as main: bind first, then apply the filter.
+        let listener = args.rpc_listen.as_ref().unwrap().bind()?;
+        let zuk = Yozuk::builder().build();
+        thread::spawn(move || -> Result<()> {
+            secure_context(&args, None)?.apply_to_current_thread()?;
+            listener.serve(zuk)
+        });
+
+        let mode = fs::metadata(&path)?.permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        let mut stream = BufReader::new(UnixStream::connect(&path)?);
+        stream.get_mut().write_all(
+            br#"{"jsonrpc":"2.0","id":1,"method":"get_commands","params":{"input":"1 + 1"}}"#,
+        )?;
+        stream.get_mut().write_all(b"\n")?;
+        let mut line = String::new();
+        stream.read_line(&mut line)?;
+        assert!(line.contains("yozuk-skill-calc"), "{}", line);
+        Ok(())
+    }
+}
@@ -0,0 +1,442 @@
+use anyhow::Result;
+use std::io::{self, Write};
+use unicode_width::UnicodeWidthChar;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Key {
+    Quit,
+    Down,
+    Up,
+    PageDown,
+    PageUp,
+    Top,
+    Bottom,
+    Search,
+    Next,
+    Prev,
+    Enter,
+    Backspace,
+    Escape,
+    Char(char),
+    Unknown,
+}
+
+fn parse_key(buf: &[u8]) -> (Key, usize) {
+    match buf {
+        [b'\x1b', b'[', b'A', ..] => (Key::Up, 3),
+        [b'\x1b', b'[', b'B', ..] => (Key::Down, 3),
+        [b'\x1b', b'[', b'H', ..] => (Key::Top, 3),
+        [b'\x1b', b'[', b'F', ..] => (Key::Bottom, 3),
+        [b'\x1b', b'[', b'5', b'~', ..] => (Key::PageUp, 4),
+        [b'\x1b', b'[', b'6', b'~', ..] => (Key::PageDown, 4),
+        [b'\x1b', b'[', ..] => (Key::Unknown, 2),
+        [b'\x1b', ..] => (Key::Escape, 1),
+        [b'q' | b'Q' | b'\x03', ..] => (Key::Quit, 1),
+        [b'j' | b'e', ..] => (Key::Down, 1),
+        [b'k' | b'y', ..] => (Key::Up, 1),
+        [b' ' | b'f', ..] => (Key::PageDown, 1),
+        [b'b', ..] => (Key::PageUp, 1),
+        [b'g' | b'<', ..] => (Key::Top, 1),
+        [b'G' | b'>', ..] => (Key::Bottom, 1),
+        [b'/', ..] => (Key::Search, 1),
+        [b'n', ..] => (Key::Next, 1),
+        [b'N', ..] => (Key::Prev, 1),
+        [b'\r' | b'\n', ..] => (Key::Enter, 1),
+        [b'\x7f' | b'\x08', ..] => (Key::Backspace, 1),
+        _ => parse_char(buf),
+    }
+}
+
+fn parse_text_key(buf: &[u8]) -> (Key, usize) {
+    match buf {
+        [b'\x1b', b'[', ..] => parse_key(buf),
+        [b'\x1b', ..] => (Key::Escape, 1),
+        [b'\x03', ..] => (Key::Escape, 1),
+        [b'\r' | b'\n', ..] => (Key::Enter, 1),
+        [b'\x7f' | b'\x08', ..] => (Key::Backspace, 1),
+        _ => parse_char(buf),
+    }
+}
+
+fn parse_char(buf: &[u8]) -> (Key, usize) {
+    if buf.is_empty() {
+        return (Key::Unknown, 0);
+    }
+    let len = (1..=buf.len().min(4))
+        .find(|&len| std::str::from_utf8(&buf[..len]).is_ok())
+        .unwrap_or(1);
+    let key = std::str::from_utf8(&buf[..len])
+        .ok()
+        .and_then(|s| s.chars().next())
+        .map_or(Key::Unknown, Key::Char);
+    (key, len)
+}
+
+fn wrap(line: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![String::new()];
+    let mut column = 0;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        let current = lines.last_mut().unwrap();
+        if c == '\x1b' {
+            current.push(c);
+            for c in chars.by_ref() {
+                current.push(c);
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+            continue;
+        }
+        let char_width = c.width().unwrap_or(0);
+        if width > 0 && column + char_width > width {
+            lines.push(String::new());
+            column = 0;
+        }
+        lines.last_mut().unwrap().push(c);
+        column += char_width;
+    }
+    lines
+}
+
+fn strip_ansi(line: &str) -> String {
+    let mut plain = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+struct Document {
+    lines: Vec<String>,
+    plain: Vec<String>,
+}
+
+impl Document {
+    fn new(text: &str, width: usize) -> Self {
+        let lines = text
+            .lines()
+            .flat_map(|line| wrap(line, width))
+            .collect::<Vec<_>>();
+        let plain = lines.iter().map(|line| strip_ansi(line)).collect();
+        Self { lines, plain }
+    }
+
+    fn find(&self, query: &str, from: usize, forward: bool) -> Option<usize> {
+        let query = query.to_lowercase();
+        let matches = |index: &usize| self.plain[*index].to_lowercase().contains(&query);
+        if forward {
+            (from..self.plain.len()).find(matches)
+        } else {
+            (0..=from.min(self.plain.len().saturating_sub(1)))
+                .rev()
+                .find(matches)
+        }
+    }
+}
+
+pub fn page(data: &[u8]) -> Result<()> {
+    let (width, height) = term_size();
+    let text = String::from_utf8_lossy(data);
+    let doc = Document::new(&text, width);
+    if height == 0 || doc.lines.len() < height {
+        let mut stdout = io::stdout();
+        stdout.write_all(data)?;
+        stdout.flush()?;
+        return Ok(());
+    }
+
+    if let Some(command) = external_pager() {
+        if run_external(&command, data).is_ok() {
+            return Ok(());
+        }
+    }
+
+    builtin::run(&text, doc, width)
+}
+
+// The seccomp filter of secure-context builds forbids spawning processes,
+// so $PAGER is ignored there. This is documented in `--help`.
+fn external_pager() -> Option<Vec<String>> {
+    if cfg!(any(
+        target_arch = "wasm32",
+        all(
+            target_os = "linux",
+            target_arch = "x86_64",
+            feature = "secure-context"
+        )
+    )) {
+        return None;
+    }
+    let pager = std::env::var("PAGER").ok()?;
+    shell_words::split(&pager)
+        .ok()
+        .filter(|args| !args.is_empty())
+}
+
+fn run_external(command: &[String], data: &[u8]) -> Result<()> {
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new(&command[0])
+        .args(&command[1..])
+        .env(
+            "LESS",
+            std::env::var("LESS").unwrap_or_else(|_| "FRX".into()),
+        )
+        .stdin(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(data);
+    }
+    child.wait()?;
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn term_size() -> (usize, usize) {
+    crossterm::terminal::size()
+        .map(|(cols, rows)| (cols as usize, rows as usize))
+        .unwrap_or((0, 0))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn term_size() -> (usize, usize) {
+    (0, 0)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod builtin {
+    use super::{parse_key, parse_text_key, Document, Key};
+    use anyhow::Result;
+    use crossterm::{
+        cursor::{Hide, MoveTo, Show},
+        style::{Attribute, Print, SetAttribute},
+        terminal::{
+            self, disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen,
+            LeaveAlternateScreen,
+        },
+        QueueableCommand,
+    };
+    use std::io::{stdin, stdout, Read, Write};
+
+    const DISABLE_AUTOWRAP: &str = "\x1b[?7l";
+    const ENABLE_AUTOWRAP: &str = "\x1b[?7h";
+
+    struct Pager<'a> {
+        text: &'a str,
+        width: usize,
+        doc: Document,
+        top: usize,
+        rows: usize,
+        query: String,
+        input: Option<String>,
+        message: String,
+    }
+
+    pub fn run(text: &str, doc: Document, width: usize) -> Result<()> {
+        let mut stdout = stdout().lock();
+        enable_raw_mode()?;
+        stdout
+            .queue(EnterAlternateScreen)?
+            .queue(Hide)?
+            .queue(Print(DISABLE_AUTOWRAP))?;
+
+        let result = Pager {
+            text,
+            width,
+            doc,
+            top: 0,
+            rows: 0,
+            query: String::new(),
+            input: None,
+            message: String::new(),
+        }
+        .event_loop(&mut stdout);
+
+        stdout
+            .queue(Print(ENABLE_AUTOWRAP))?
+            .queue(Show)?
+            .queue(LeaveAlternateScreen)?;
+        stdout.flush()?;
+        disable_raw_mode()?;
+        result
+    }
+
+    impl<'a> Pager<'a> {
+        fn event_loop<W: Write>(&mut self, stdout: &mut W) -> Result<()> {
+            let mut stdin = stdin().lock();
+            let mut buf = [0; 64];
+            let mut pending = Vec::new();
+            loop {
+                let (width, rows) = terminal::size()
+                    .map(|(cols, rows)| (cols as usize, rows as usize))
+                    .unwrap_or((self.width, 24));
+                if width != self.width {
+                    self.width = width;
+                    self.doc = Document::new(self.text, width);
+                }
+                self.rows = rows.saturating_sub(1).max(1);
+                self.top = self.top.min(self.max_top());
+                self.draw(stdout)?;
+
+                let n = stdin.read(&mut buf)?;
+                if n == 0 {
+                    return Ok(());
+                }
+                pending.extend_from_slice(&buf[..n]);
+                while !pending.is_empty() {
+                    let (key, len) = if self.input.is_some() {
+                        parse_text_key(&pending)
+                    } else {
+                        parse_key(&pending)
+                    };
+                    pending.drain(..len.max(1));
+                    if !self.handle(key) {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+
+        fn max_top(&self) -> usize {
+            self.doc.lines.len().saturating_sub(self.rows)
+        }
+
+        fn handle(&mut self, key: Key) -> bool {
+            if let Some(input) = &mut self.input {
+                match key {
+                    Key::Enter => {
+                        self.query = self.input.take().unwrap_or_default();
+                        self.search(self.top, true);
+                    }
+                    Key::Escape => self.input = None,
+                    Key::Backspace => {
+                        input.pop();
+                    }
+                    Key::Char(c) => input.push(c),
+                    _ => {}
+                }
+                return true;
+            }
+
+            self.message.clear();
+            match key {
+                Key::Quit => return false,
+                Key::Down | Key::Enter => self.top = (self.top + 1).min(self.max_top()),
+                Key::Up => self.top = self.top.saturating_sub(1),
+                Key::PageDown => self.top = (self.top + self.rows).min(self.max_top()),
+                Key::PageUp => self.top = self.top.saturating_sub(self.rows),
+                Key::Top => self.top = 0,
+                Key::Bottom => self.top = self.max_top(),
+                Key::Search => self.input = Some(String::new()),
+                Key::Next => self.search(self.top + 1, true),
+                Key::Prev => self.search(self.top.saturating_sub(1), false),
+                _ => {}
+            }
+            true
+        }
+
+        fn search(&mut self, from: usize, forward: bool) {
+            if self.query.is_empty() {
+                return;
+            }
+            match self.doc.find(&self.query, from, forward) {
+                Some(line) => self.top = line,
+                None => self.message = format!("Pattern not found: {}", self.query),
+            }
+        }
+
+        fn draw<W: Write>(&self, stdout: &mut W) -> Result<()> {
+            let lines = self.doc.lines.iter().skip(self.top).take(self.rows);
+            for (row, line) in lines.enumerate() {
+                stdout
+                    .queue(MoveTo(0, row as u16))?
+                    .queue(Clear(ClearType::CurrentLine))?
+                    .queue(Print(line))?
+                    .queue(SetAttribute(Attribute::Reset))?;
+            }
+            for row in self.doc.lines.len().saturating_sub(self.top)..self.rows {
+                stdout
+                    .queue(MoveTo(0, row as u16))?
+                    .queue(Clear(ClearType::CurrentLine))?
+                    .queue(Print("~"))?;
+            }
+
+            let status = if let Some(input) = &self.input {
+                format!("/{}", input)
+            } else if !self.message.is_empty() {
+                self.message.clone()
+            } else {
+                let last = (self.top + self.rows).min(self.doc.lines.len());
+                format!(
+                    "lines {}-{}/{} (q: quit, /: search)",
+                    self.top + 1,
+                    last,
+                    self.doc.lines.len()
+                )
+            };
+            stdout
+                .queue(MoveTo(0, self.rows as u16))?
+                .queue(Clear(ClearType::CurrentLine))?
+                .queue(SetAttribute(Attribute::Reverse))?
+                .queue(Print(status))?
+                .queue(SetAttribute(Attribute::Reset))?;
+            stdout.flush()?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod builtin {
+    use super::Document;
+    use anyhow::Result;
+    use std::io::{self, Write};
+
+    pub fn run(_text: &str, doc: Document, _width: usize) -> Result<()> {
+        let mut stdout = io::stdout();
+        for line in doc.lines {
+            writeln!(stdout, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(parse_key(b"q"), (Key::Quit, 1));
+        assert_eq!(parse_key(b"\x1b[Bj"), (Key::Down, 3));
+        assert_eq!(parse_key(b"\x1b[6~"), (Key::PageDown, 4));
+        assert_eq!(parse_key(b"\x1b"), (Key::Escape, 1));
+        assert_eq!(parse_key("あ".as_bytes()), (Key::Char('あ'), 3));
+        assert_eq!(parse_text_key(b"q"), (Key::Char('q'), 1));
+        assert_eq!(parse_text_key(b"\r"), (Key::Enter, 1));
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap("abcdefg", 3), ["abc", "def", "g"]);
+        assert_eq!(wrap("\x1b[2mabcd\x1b[0m", 2), ["\x1b[2mab", "cd\x1b[0m"]);
+        assert_eq!(wrap("あいう", 4), ["あい", "う"]);
+        assert_eq!(wrap("", 4), [""]);
+    }
+
+    #[test]
+    fn test_find() {
+        let doc = Document::new("alpha\n\x1b[1mBeta\x1b[0m\ngamma\nbeta\n", 80);
+        assert_eq!(doc.plain[1], "Beta");
+        assert_eq!(doc.find("beta", 0, true), Some(1));
+        assert_eq!(doc.find("beta", 2, true), Some(3));
+        assert_eq!(doc.find("beta", 2, false), Some(1));
+        assert_eq!(doc.find("delta", 0, true), None);
+    }
+}
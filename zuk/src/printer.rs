@@ -1,5 +1,8 @@
-use crate::term;
-use crate::Args;
+use crate::args::{Args, OutputFormat};
+use crate::files;
+use crate::json;
+use crate::pager;
+use crate::term::{self, ImageProtocol};
 use anyhow::Result;
 use base64::write::EncoderWriter;
 use hexyl::{BorderStyle, Printer};
@@ -43,8 +46,81 @@ impl<'a> TerminalPrinter<'a> {
         Ok(())
     }
 
-    pub fn print_result(&self, output: &Output) -> Result<()> {
-        let mut stdout = io::stdout();
+    pub fn print_outputs(&self, outputs: &[Output]) -> Result<()> {
+        if let Some(dst) = &self.args.output {
+            let blocks = outputs
+                .iter()
+                .flat_map(|output| &output.blocks)
+                .filter_map(|block| match block {
+                    Block::Data(data) => Some(data),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            for path in files::write_data_blocks(dst, &blocks)? {
+                if self.args.verbose > 0 {
+                    eprintln!("{}", format!("Saved to {}", path.display()).dimmed());
+                }
+            }
+        }
+
+        match self.args.format {
+            OutputFormat::Text if self.is_paging_enabled(outputs) => {
+                let mut buf = Vec::new();
+                self.print_text(outputs, &mut buf, false)?;
+                pager::page(&buf)?;
+            }
+            OutputFormat::Text => {
+                self.print_text(outputs, &mut io::stdout(), true)?;
+            }
+            OutputFormat::Json => {
+                let mut stdout = io::stdout();
+                serde_json::to_writer(&mut stdout, &outputs)?;
+                writeln!(&mut stdout)?;
+            }
+            OutputFormat::Raw => {
+                if self.args.output.is_none() {
+                    let mut stdout = io::stdout();
+                    for block in outputs.iter().flat_map(|output| &output.blocks) {
+                        if let Block::Data(data) = block {
+                            stdout.write_all(&data.data)?;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn is_paging_enabled(&self, outputs: &[Output]) -> bool {
+        !self.args.no_pager
+            && self.args.output.is_none()
+            && term::is_stdout_tty()
+            && term::is_stdin_tty()
+            && outputs
+                .iter()
+                .flat_map(|output| &output.blocks)
+                .all(|block| match block {
+                    Block::Data(data) => {
+                        !data.media_type.as_str().starts_with("image/")
+                            && !yozuk_helper_filetype::guess_media_type(&data.data)
+                                .as_str()
+                                .starts_with("image/")
+                    }
+                    _ => true,
+                })
+    }
+
+    fn print_text<W: Write>(&self, outputs: &[Output], stdout: &mut W, images: bool) -> Result<()> {
+        for (i, output) in outputs.iter().enumerate() {
+            if i > 0 {
+                writeln!(stdout, "--")?;
+            }
+            self.print_result(output, stdout, images)?;
+        }
+        Ok(())
+    }
+
+    fn print_result<W: Write>(&self, output: &Output, stdout: &mut W, images: bool) -> Result<()> {
         let mut stderr = io::stderr();
 
         let title = if output.title.is_empty() {
@@ -78,8 +154,13 @@ impl<'a> TerminalPrinter<'a> {
                         writeln!(&mut stderr, "{}{}", title, comment.text)?;
                     }
                 }
+                Block::Data(_) if self.args.output.is_some() => {}
                 Block::Data(data) => {
                     if yozuk_helper_filetype::is_utf8_text(&data.data) {
+                        if let Some(node) = self.json_node(data) {
+                            node.render(&mut *stdout)?;
+                            continue;
+                        }
                         if data.highlights.is_empty() {
                             stdout.write_all(&data.data)?;
                         } else {
@@ -88,18 +169,18 @@ impl<'a> TerminalPrinter<'a> {
                                 if let Ok(s) =
                                     std::str::from_utf8(&data.data[offset..hl.range.start])
                                 {
-                                    write!(&mut stdout, "{}", s.dimmed())?;
+                                    write!(stdout, "{}", s.dimmed())?;
                                 }
                                 stdout.write_all(&data.data[hl.range.clone()])?;
                                 offset = hl.range.end;
                             }
                             if let Ok(s) = std::str::from_utf8(&data.data[offset..]) {
-                                write!(&mut stdout, "{}", s.dimmed())?;
+                                write!(stdout, "{}", s.dimmed())?;
                             }
                         }
-                        writeln!(&mut stdout)?;
+                        writeln!(stdout)?;
                     } else {
-                        self.print_binary(data)?;
+                        self.print_binary(data, stdout, images)?;
                     }
                 }
                 _ => {
@@ -113,6 +194,29 @@ impl<'a> TerminalPrinter<'a> {
         Ok(())
     }
 
+    fn json_node(&self, data: &block::Data) -> Option<json::Node> {
+        if data.media_type == media_type!(APPLICATION / JSON)
+            && data.highlights.is_empty()
+            && term::is_stdout_tty()
+        {
+            json::Node::parse(&data.data)
+        } else {
+            None
+        }
+    }
+
+    pub fn print_result_numbers(&self, numbers: &[usize]) -> Result<()> {
+        if !numbers.is_empty() {
+            let labels = numbers
+                .iter()
+                .map(|n| format!("${}", n))
+                .collect::<Vec<_>>()
+                .join(" ");
+            eprintln!("{}", labels.dimmed());
+        }
+        Ok(())
+    }
+
     pub fn print_error(&self, outputs: &[Output]) -> Result<()> {
         let mut stderr = io::stderr();
 
@@ -150,17 +254,28 @@ impl<'a> TerminalPrinter<'a> {
 
     fn print_image(&self, data: &block::Data) -> Result<bool> {
         let media_type = &data.media_type;
-        if (media_type == media_type!(IMAGE / PNG)
-            || media_type == media_type!(IMAGE / GIF)
-            || media_type == media_type!(IMAGE / JPEG))
-            && term::is_iterm2_image_supported()
-        {
-            term::iterm2_image_show(&*data.data, Some(&data.file_name))?;
-            return Ok(true);
-        }
-        if media_type == media_type!(IMAGE / PNG) && term::is_kitty_image_supported() {
-            term::kitty_image_show_png(&*data.data)?;
-            return Ok(true);
+        // Probe the terminal only when there is an image to draw.
+        #[cfg(not(target_arch = "wasm32"))]
+        let drawable = image::guess_format(&data.data).is_ok();
+        #[cfg(target_arch = "wasm32")]
+        let drawable = false;
+        let protocol = if drawable {
+            term::image_protocol()
+        } else {
+            ImageProtocol::Blocks
+        };
+        match protocol {
+            ImageProtocol::Iterm2
+                if media_type == media_type!(IMAGE / PNG)
+                    || media_type == media_type!(IMAGE / GIF)
+                    || media_type == media_type!(IMAGE / JPEG) =>
+            {
+                term::iterm2_image_show(&*data.data, Some(&data.file_name))?;
+                return Ok(true);
+            }
+            ImageProtocol::Kitty if term::kitty_image_show(&data.data)? => return Ok(true),
+            ImageProtocol::Sixel if term::sixel_image_show(&data.data)? => return Ok(true),
+            _ => {}
         }
 
         #[cfg(not(target_arch = "wasm32"))]
@@ -225,10 +340,14 @@ impl<'a> TerminalPrinter<'a> {
         Ok(false)
     }
 
-    fn print_binary(&self, data: &block::Data) -> Result<()> {
-        let mut stdout = io::stdout();
+    fn print_binary<W: Write>(
+        &self,
+        data: &block::Data,
+        stdout: &mut W,
+        images: bool,
+    ) -> Result<()> {
         if term::is_stdout_tty() {
-            if self.print_image(data)? {
+            if images && self.print_image(data)? {
                 return Ok(());
             }
             match data.display.binary {
@@ -239,7 +358,7 @@ impl<'a> TerminalPrinter<'a> {
                     let use_squeezing = false;
                     let border_style = BorderStyle::Unicode;
                     let mut printer = Printer::new(
-                        &mut stdout,
+                        &mut *stdout,
                         show_color,
                         show_char_panel,
                         show_position_panel,
@@ -256,7 +375,7 @@ impl<'a> TerminalPrinter<'a> {
                 }
                 _ => {
                     {
-                        let mut enc = EncoderWriter::new(&mut stdout, base64::STANDARD);
+                        let mut enc = EncoderWriter::new(&mut *stdout, base64::STANDARD);
                         enc.write_all(&*data.data)?;
                         enc.finish()?;
                     }
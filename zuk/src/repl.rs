@@ -9,27 +9,71 @@ use rustyline::validate::{self, Validator};
 use rustyline::{Context, Editor};
 use rustyline_derive::Helper;
 use std::borrow::Cow;
+use std::cell::RefCell;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Read, Seek, Write};
+use yozuk::Yozuk;
+use yozuk_sdk::prelude::*;
 
-pub struct Repl {
-    editor: Editor<YozukHelper>,
+pub struct Repl<'a> {
+    editor: Editor<YozukHelper<'a>>,
+    history_file: Option<File>,
 }
 
 const PROMPT: &str = "» ";
+const MAX_COMPLETIONS: usize = 100;
+const MAX_HINT_SUGGESTIONS: usize = 10;
 
-impl Repl {
-    pub fn new() -> Self {
+// The history file is opened before entering the secure context,
+// which does not allow opening files for writing.
+pub fn open_history() -> Option<File> {
+    let dir = dirs_next::data_dir()?.join("yozuk");
+    fs::create_dir_all(&dir).ok()?;
+    let mut options = OpenOptions::new();
+    options.read(true).write(true).create(true).truncate(false);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        let file = options.mode(0o600).open(dir.join("history")).ok()?;
+        file.set_permissions(fs::Permissions::from_mode(0o600))
+            .ok()?;
+        Some(file)
+    }
+    #[cfg(not(unix))]
+    options.open(dir.join("history")).ok()
+}
+
+impl<'a> Repl<'a> {
+    pub fn new(zuk: &'a Yozuk, mut history_file: Option<File>) -> Self {
         let mut editor = Editor::new();
 
         let helper = YozukHelper {
+            zuk,
             highlighter: MatchingBracketHighlighter::new(),
             hinter: HistoryHinter {},
             colored_prompt: "".to_owned(),
+            default_hint: zuk.random_suggestions(1).pop(),
+            hint_cache: RefCell::new(None),
         };
         editor.set_helper(Some(helper));
         editor.helper_mut().expect("No helper").colored_prompt =
             format!("{}", PROMPT.bold().blue());
 
-        Self { editor }
+        if let Some(file) = &mut history_file {
+            let mut history = String::new();
+            let _ = file.read_to_string(&mut history);
+            for line in history
+                .lines()
+                .filter(|line| !line.is_empty() && *line != "#V2")
+            {
+                editor.add_history_entry(line);
+            }
+        }
+
+        Self {
+            editor,
+            history_file,
+        }
     }
 
     pub fn readline(&mut self) -> Option<String> {
@@ -48,26 +92,109 @@ impl Repl {
     }
 }
 
+impl<'a> Drop for Repl<'a> {
+    fn drop(&mut self) {
+        if let Some(file) = &mut self.history_file {
+            let history = self.editor.history().iter();
+            if let Err(err) = save_history(file, history) {
+                eprintln!("Failed to save history: {}", err);
+            }
+        }
+    }
+}
+
+fn save_history<'a, I>(file: &mut File, history: I) -> io::Result<()>
+where
+    I: Iterator<Item = &'a String>,
+{
+    file.rewind()?;
+    file.set_len(0)?;
+    let mut writer = BufWriter::new(file);
+    for line in history {
+        writeln!(writer, "{}", line)?;
+    }
+    writer.flush()
+}
+
 #[derive(Helper)]
-struct YozukHelper {
+struct YozukHelper<'a> {
+    zuk: &'a Yozuk,
     highlighter: MatchingBracketHighlighter,
     hinter: HistoryHinter,
     colored_prompt: String,
+    default_hint: Option<String>,
+    hint_cache: RefCell<Option<(String, Vec<String>)>>,
 }
 
-impl Completer for YozukHelper {
+impl<'a> Completer for YozukHelper<'a> {
     type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let (start, candidates) = self.zuk.completions(line, pos, MAX_COMPLETIONS);
+        Ok((
+            start,
+            candidates
+                .into_iter()
+                .map(|word| Pair {
+                    display: word.clone(),
+                    replacement: word,
+                })
+                .collect(),
+        ))
+    }
 }
 
-impl Hinter for YozukHelper {
+impl<'a> Hinter for YozukHelper<'a> {
     type Hint = String;
 
     fn hint(&self, line: &str, pos: usize, ctx: &Context<'_>) -> Option<String> {
-        self.hinter.hint(line, pos, ctx)
+        if let Some(hint) = self.hinter.hint(line, pos, ctx) {
+            return Some(hint);
+        }
+        if pos < line.len() || line.starts_with(':') {
+            return None;
+        }
+        if line.is_empty() {
+            return self.default_hint.clone();
+        }
+
+        // Reuse the suggestions while the line keeps matching one of them.
+        let mut cache = self.hint_cache.borrow_mut();
+        if let Some((prefix, suggestions)) = &*cache {
+            if line.starts_with(prefix.as_str()) {
+                if let Some(hint) = suggestion_hint(line, suggestions) {
+                    return Some(hint);
+                }
+            }
+        }
+
+        let tokens = Tokenizer::new().tokenize(line);
+        let suggestions = self.zuk.suggestions(&tokens, &[], MAX_HINT_SUGGESTIONS);
+        let hint = suggestion_hint(line, &suggestions);
+        *cache = Some((line.to_string(), suggestions));
+        hint
     }
 }
 
-impl Highlighter for YozukHelper {
+fn suggestion_hint(line: &str, suggestions: &[String]) -> Option<String> {
+    let prefix = line.to_lowercase();
+    suggestions
+        .iter()
+        .filter(|suggestion| suggestion.len() > line.len())
+        .find(|suggestion| {
+            suggestion
+                .get(..line.len())
+                .is_some_and(|head| head.to_lowercase() == prefix)
+        })
+        .map(|suggestion| suggestion[line.len()..].to_string())
+}
+
+impl<'a> Highlighter for YozukHelper<'a> {
     fn highlight_prompt<'b, 's: 'b, 'p: 'b>(
         &'s self,
         prompt: &'p str,
@@ -93,7 +220,7 @@ impl Highlighter for YozukHelper {
     }
 }
 
-impl Validator for YozukHelper {
+impl<'a> Validator for YozukHelper<'a> {
     fn validate(
         &self,
         _ctx: &mut validate::ValidationContext,
@@ -105,3 +232,37 @@ impl Validator for YozukHelper {
         false
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggestion_hint() {
+        let suggestions = ["Generate 3 UUIDs".to_string(), "generate uuid".to_string()];
+        assert_eq!(
+            suggestion_hint("generate u", &suggestions).as_deref(),
+            Some("uid")
+        );
+        assert_eq!(
+            suggestion_hint("generate ", &suggestions).as_deref(),
+            Some("3 UUIDs")
+        );
+        assert_eq!(suggestion_hint("roll", &suggestions), None);
+        assert_eq!(suggestion_hint("generate uuid", &suggestions), None);
+    }
+
+    #[test]
+    fn test_save_history() -> io::Result<()> {
+        let mut file = tempfile::tempfile()?;
+        let history = ["generate uuid".to_string(), "1 + 1".to_string()];
+        save_history(&mut file, history.iter())?;
+        save_history(&mut file, history[1..].iter())?;
+
+        let mut data = String::new();
+        file.rewind()?;
+        file.read_to_string(&mut data)?;
+        assert_eq!(data, "1 + 1\n");
+        Ok(())
+    }
+}
@@ -1,46 +1,49 @@
 #![cfg(all(feature = "rpc", not(target_arch = "wasm32")))]
 
-use anyhow::Result;
-use json_rpc2::{Request, Response, Server, Service};
-use mediatype::media_type;
+use crate::chooser::skill_name;
+use anyhow::{bail, Result};
+use json_rpc2::{Request, Response, RpcError, Server, Service};
+use mediatype::{media_type, MediaTypeBuf};
 use serde_derive::{Deserialize, Serialize};
 use serde_json::{de::IoRead, Deserializer};
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
-use std::io::{Cursor, Read, Write};
+use std::io::{self, Cursor, Read, Write};
+use std::mem;
+use std::net::{SocketAddr, TcpListener, ToSocketAddrs};
 use std::path::PathBuf;
-use std::sync::Mutex;
+use std::str::FromStr;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
 use yozuk::Yozuk;
 use yozuk_sdk::prelude::*;
 
+const REQUEST_CANCELLED: isize = -32800;
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+const DEFAULT_WORKERS: usize = 4;
+const MAX_QUEUED_REQUESTS: usize = 64;
+
+type SharedStreams = Arc<Mutex<Vec<InputStream>>>;
+
+struct RequestContext {
+    streams: SharedStreams,
+    user: Option<UserContext>,
+    history: Arc<Mutex<Vec<CommandArgs>>>,
+}
+
 struct ServiceHandler {
-    streams: Mutex<Vec<InputStream>>,
+    zuk: Arc<Yozuk>,
 }
 
 impl Service for ServiceHandler {
-    type Data = Yozuk;
-    fn handle(&self, request: &Request, zuk: &Self::Data) -> json_rpc2::Result<Option<Response>> {
+    type Data = RequestContext;
+    fn handle(&self, request: &Request, ctx: &Self::Data) -> json_rpc2::Result<Option<Response>> {
+        let zuk = &self.zuk;
+        let streams = &ctx.streams;
         let response = match request.method() {
-            "set_streams" => {
-                let mut streams = self.streams.lock().unwrap();
-                let req: Vec<Stream> = request.deserialize()?;
-                *streams = req
-                    .into_iter()
-                    .filter_map(|data| match data {
-                        Stream::Base64 { base64 } => base64::decode(base64).ok().map(|data| {
-                            InputStream::new(
-                                Cursor::new(data),
-                                media_type!(APPLICATION / OCTET_STREAM),
-                            )
-                        }),
-                        Stream::File { path } => File::open(path).ok().map(|file| {
-                            InputStream::new(file, media_type!(APPLICATION / OCTET_STREAM))
-                        }),
-                    })
-                    .collect();
-                Some((request, serde_json::Value::Null).into())
-            }
             "get_commands" => {
-                let streams = self.streams.lock().unwrap();
+                let streams = streams.lock().unwrap();
                 let req: GetCommandsRequest = request.deserialize()?;
                 let tokens: Vec<Token> = req.into();
                 let commands = zuk.get_commands(&tokens, &streams);
@@ -48,44 +51,538 @@ impl Service for ServiceHandler {
                 Some((request, serde_json::to_value(res).unwrap()).into())
             }
             "run_commands" => {
-                let mut streams = self.streams.lock().unwrap();
+                let mut streams = streams.lock().unwrap();
                 let req: RunCommandsRequest = request.deserialize()?;
-                let result: RunCommandsResponse = zuk
-                    .run_commands(req.commands, &mut streams, Some(&req.user))
-                    .into();
+                let user = req.user.as_ref().or(ctx.user.as_ref());
+                ctx.history
+                    .lock()
+                    .unwrap()
+                    .extend(req.commands.iter().cloned());
+                let result: RunCommandsResponse =
+                    zuk.run_commands(req.commands, &mut streams, user).into();
                 streams.clear();
                 Some((request, serde_json::to_value(result).unwrap()).into())
             }
+            "completions" => {
+                let req: CompletionsRequest = request.deserialize()?;
+                let pos = req.pos.unwrap_or(req.input.len());
+                let (start, candidates) = zuk.completions(&req.input, pos, req.amount);
+                let res = CompletionsResponse { start, candidates };
+                Some((request, serde_json::to_value(res).unwrap()).into())
+            }
+            "suggestions" => {
+                let streams = streams.lock().unwrap();
+                let req: SuggestionsRequest = request.deserialize()?;
+                let amount = req.amount;
+                let tokens: Vec<Token> = GetCommandsRequest::from(req).into();
+                let suggestions = zuk.suggestions(&tokens, &streams, amount);
+                let res = SuggestionsResponse { suggestions };
+                Some((request, serde_json::to_value(res).unwrap()).into())
+            }
+            "random_suggestions" => {
+                let req: RandomSuggestionsRequest = request.deserialize()?;
+                let suggestions = zuk.random_suggestions(req.amount);
+                let res = SuggestionsResponse { suggestions };
+                Some((request, serde_json::to_value(res).unwrap()).into())
+            }
+            "history" => {
+                let commands = ctx.history.lock().unwrap().clone();
+                let res = HistoryResponse { commands };
+                Some((request, serde_json::to_value(res).unwrap()).into())
+            }
+            "skills" => {
+                let skills = zuk
+                    .skills()
+                    .map(|key| SkillInfo {
+                        key: key.into(),
+                        name: skill_name(key).into(),
+                        title: zuk.skill_title(key).unwrap_or_default().into(),
+                    })
+                    .collect();
+                let res = SkillsResponse { skills };
+                Some((request, serde_json::to_value(res).unwrap()).into())
+            }
+            "explain" => {
+                let streams = streams.lock().unwrap();
+                let req: GetCommandsRequest = request.deserialize()?;
+                let tokens: Vec<Token> = req.into();
+                let skills = zuk
+                    .explain(&tokens, &streams)
+                    .into_iter()
+                    .map(|exp| SkillExplanation {
+                        title: zuk.skill_title(&exp.skill).unwrap_or_default().into(),
+                        skill: exp.skill,
+                        confidence: exp.confidence,
+                        tokens: exp.tokens,
+                        command: exp.command,
+                    })
+                    .collect();
+                let res = ExplainResponse { tokens, skills };
+                Some((request, serde_json::to_value(res).unwrap()).into())
+            }
             _ => None,
         };
         Ok(response)
     }
 }
 
-pub fn start_server<R, W>(zuk: Yozuk, reader: R, mut writer: W) -> Result<()>
+pub fn start_server<R, W>(zuk: Yozuk, reader: R, writer: W) -> Result<()>
 where
     R: Read,
-    W: Write,
+    W: Write + Send,
 {
-    let service: Box<dyn Service<Data = Yozuk>> = Box::new(ServiceHandler {
-        streams: Mutex::new(Vec::new()),
-    });
+    serve(Arc::new(zuk), reader, writer, true)
+}
+
+fn serve<R, W>(zuk: Arc<Yozuk>, reader: R, writer: W, allow_files: bool) -> Result<()>
+where
+    R: Read,
+    W: Write + Send,
+{
+    let service: Box<dyn Service<Data = RequestContext>> = Box::new(ServiceHandler { zuk });
     let server = Server::new(vec![&service]);
+    let writer = Mutex::new(writer);
+    let in_flight = Mutex::new(HashSet::new());
+    let history = Arc::new(Mutex::new(Vec::new()));
+    let mut store = StreamStore::new(allow_files);
+    let mut streams = SharedStreams::default();
+    let mut user = None;
+
     let reader = IoRead::new(reader);
-    let stream = Deserializer::new(reader).into_iter::<Request>();
-    for request in stream {
-        let response = server.serve(&request?, &zuk);
-        serde_json::to_writer(&mut writer, &response)?;
-        writeln!(&mut writer)?;
+    let requests = Deserializer::new(reader).into_iter::<Request>();
+
+    // Requests are handled by a fixed pool of workers. Once the queue is full,
+    // reading stops until a worker is free.
+    let workers = thread::available_parallelism().map_or(DEFAULT_WORKERS, usize::from);
+    let (sender, receiver) =
+        mpsc::sync_channel::<(Request, RequestContext, Option<String>)>(MAX_QUEUED_REQUESTS);
+    let receiver = Mutex::new(receiver);
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let job = receiver.lock().unwrap().recv();
+                let Ok((request, ctx, id)) = job else {
+                    break;
+                };
+                if id
+                    .as_ref()
+                    .is_some_and(|id| !in_flight.lock().unwrap().contains(id))
+                {
+                    // Cancelled before a worker picked it up.
+                    continue;
+                }
+                let response = server.serve(&request, &ctx);
+                let cancelled = id.is_some_and(|id| !in_flight.lock().unwrap().remove(&id));
+                if let Some(response) = response.filter(|_| !cancelled) {
+                    // The peer has gone away if this fails.
+                    let _ = write_response(&writer, &response);
+                }
+            });
+        }
+
+        // Dropping the sender when reading ends lets the workers finish.
+        let sender = sender;
+        for request in requests {
+            let request = request?;
+            match request.method() {
+                "set_streams" => {
+                    let response = match request.deserialize::<Vec<Stream>>().and_then(|req| {
+                        store
+                            .open_streams(req)
+                            .map_err(|err| invalid(&request, err))
+                    }) {
+                        Ok(req) => {
+                            streams = Arc::new(Mutex::new(req));
+                            (&request, serde_json::Value::Null).into()
+                        }
+                        Err(err) => (&request, err).into(),
+                    };
+                    write_response(&writer, &response)?;
+                    continue;
+                }
+                "open_stream" => {
+                    let response = match request
+                        .deserialize::<Stream>()
+                        .and_then(|req| store.open(req).map_err(|err| invalid(&request, err)))
+                    {
+                        Ok(handle) => {
+                            let res = OpenStreamResponse { handle };
+                            (&request, serde_json::to_value(res).unwrap()).into()
+                        }
+                        Err(err) => (&request, err).into(),
+                    };
+                    write_response(&writer, &response)?;
+                    continue;
+                }
+                "release_stream" => {
+                    let response = match request.deserialize::<ReleaseStreamRequest>() {
+                        Ok(req) => {
+                            (&request, serde_json::Value::Bool(store.release(req.handle))).into()
+                        }
+                        Err(err) => (&request, err).into(),
+                    };
+                    write_response(&writer, &response)?;
+                    continue;
+                }
+                "set_user_context" => {
+                    let response = match request.deserialize::<UserContext>() {
+                        Ok(req) => {
+                            user = Some(req);
+                            (&request, serde_json::Value::Null).into()
+                        }
+                        Err(err) => (&request, err).into(),
+                    };
+                    write_response(&writer, &response)?;
+                    continue;
+                }
+                // Skills cannot be interrupted, so the request keeps running
+                // and only its response is replaced with an error.
+                "$/cancelRequest" => {
+                    if let Ok(CancelRequest { id }) = request.deserialize() {
+                        if in_flight.lock().unwrap().remove(&id.to_string()) {
+                            let err = RpcError {
+                                code: REQUEST_CANCELLED,
+                                message: "Request cancelled".into(),
+                                data: None,
+                            };
+                            let request = Request::new(Some(id), String::new(), None);
+                            write_response(&writer, &(&request, err).into())?;
+                        }
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+
+            let streams = match request.params().as_ref().and_then(|params| {
+                serde_json::from_value::<RequestStreams>(params.clone())
+                    .ok()?
+                    .streams
+            }) {
+                Some(req) => match store.open_streams(req) {
+                    Ok(req) => Arc::new(Mutex::new(req)),
+                    Err(err) => {
+                        write_response(&writer, &(&request, invalid(&request, err)).into())?;
+                        continue;
+                    }
+                },
+                None if request.method() == "run_commands" => mem::take(&mut streams),
+                None => streams.clone(),
+            };
+
+            let ctx = RequestContext {
+                streams,
+                user: user.clone(),
+                history: history.clone(),
+            };
+            let id = request.id().as_ref().map(|id| id.to_string());
+            if let Some(id) = &id {
+                in_flight.lock().unwrap().insert(id.clone());
+            }
+            if sender.send((request, ctx, id)).is_err() {
+                break;
+            }
+        }
+        Ok(())
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl FromStr for Endpoint {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Some(path) = s.strip_prefix("unix:") {
+            #[cfg(unix)]
+            return Ok(Self::Unix(path.into()));
+            #[cfg(not(unix))]
+            bail!(
+                "Unix domain sockets are not supported on this platform: {}",
+                path
+            );
+        }
+        match s.to_socket_addrs()?.find(|addr| addr.ip().is_loopback()) {
+            Some(addr) => Ok(Self::Tcp(addr)),
+            None => bail!("RPC server only listens on loopback addresses: {}", s),
+        }
+    }
+}
+
+impl Endpoint {
+    pub fn bind(&self) -> Result<Listener> {
+        match self {
+            Self::Tcp(addr) => Ok(Listener::Tcp(TcpListener::bind(addr)?)),
+            #[cfg(unix)]
+            Self::Unix(path) => Ok(Listener::Unix(UnixSocket::bind(path)?)),
+        }
+    }
+}
+
+pub enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixSocket),
+}
+
+impl Listener {
+    pub fn serve(self, zuk: Yozuk) -> Result<()> {
+        let zuk = Arc::new(zuk);
+        match &self {
+            Self::Tcp(listener) => {
+                for stream in listener.incoming() {
+                    match stream.and_then(|stream| Ok((stream.try_clone()?, stream))) {
+                        Ok((reader, writer)) => spawn_session(zuk.clone(), reader, writer),
+                        Err(err) => accept_error(err),
+                    }
+                }
+            }
+            #[cfg(unix)]
+            Self::Unix(socket) => {
+                for stream in socket.listener.incoming() {
+                    match stream.and_then(|stream| Ok((stream.try_clone()?, stream))) {
+                        Ok((reader, writer)) => spawn_session(zuk.clone(), reader, writer),
+                        Err(err) => accept_error(err),
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// A failed connection must not bring the server down, but back off
+// so that errors such as running out of file descriptors do not spin.
+fn accept_error(err: io::Error) {
+    eprintln!("Failed to accept a connection: {}", err);
+    thread::sleep(ACCEPT_BACKOFF);
+}
+
+#[cfg(unix)]
+pub struct UnixSocket {
+    listener: std::os::unix::net::UnixListener,
+    path: PathBuf,
+}
+
+#[cfg(unix)]
+impl UnixSocket {
+    fn bind(path: &std::path::Path) -> Result<Self> {
+        use signal_hook::consts::{SIGINT, SIGTERM};
+        use signal_hook::iterator::Signals;
+        use std::fs::{self, Permissions};
+        use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+        use std::os::unix::net::UnixListener;
+
+        // Remove a socket left behind by a previous server.
+        if fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_socket()) {
+            fs::remove_file(path)?;
+        }
+
+        let socket = Self {
+            listener: UnixListener::bind(path)?,
+            path: path.to_path_buf(),
+        };
+        // Only the owner may connect to the socket.
+        fs::set_permissions(path, Permissions::from_mode(0o600))?;
+
+        let mut signals = Signals::new([SIGINT, SIGTERM])?;
+        let path = socket.path.clone();
+        thread::spawn(move || {
+            if let Some(signal) = signals.forever().next() {
+                let _ = fs::remove_file(&path);
+                std::process::exit(128 + signal);
+            }
+        });
+        Ok(socket)
+    }
+}
+
+#[cfg(unix)]
+impl Drop for UnixSocket {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
     }
+}
+
+fn spawn_session<R, W>(zuk: Arc<Yozuk>, reader: R, writer: W)
+where
+    R: 'static + Read + Send,
+    W: 'static + Write + Send,
+{
+    thread::spawn(move || {
+        // Each connection has its own streams, user context and history.
+        // Files are not readable over sockets, which any local user may connect to.
+        let _ = serve(zuk, reader, writer, false);
+    });
+}
+
+fn write_response<W: Write>(writer: &Mutex<W>, response: &Response) -> Result<()> {
+    let mut writer = writer.lock().unwrap();
+    serde_json::to_writer(&mut *writer, response)?;
+    writeln!(writer)?;
+    writer.flush()?;
     Ok(())
 }
 
+fn invalid(request: &Request, data: String) -> json_rpc2::Error {
+    json_rpc2::Error::InvalidParams {
+        id: request.id().clone(),
+        data,
+    }
+}
+
+#[derive(Default)]
+struct StreamStore {
+    handles: HashMap<u64, StreamData>,
+    next_handle: u64,
+    allow_files: bool,
+}
+
+impl StreamStore {
+    fn new(allow_files: bool) -> Self {
+        Self {
+            allow_files,
+            ..Default::default()
+        }
+    }
+
+    fn open(&mut self, stream: Stream) -> Result<u64, String> {
+        let data = self.resolve(stream)?;
+        self.next_handle += 1;
+        self.handles.insert(self.next_handle, data);
+        Ok(self.next_handle)
+    }
+
+    fn release(&mut self, handle: u64) -> bool {
+        self.handles.remove(&handle).is_some()
+    }
+
+    fn open_streams(&self, streams: Vec<Stream>) -> Result<Vec<InputStream>, String> {
+        streams
+            .into_iter()
+            .map(|stream| self.resolve(stream)?.open())
+            .collect()
+    }
+
+    fn resolve(&self, stream: Stream) -> Result<StreamData, String> {
+        let (source, default_type, default_name) = match stream.source {
+            StreamSource::Base64 { base64 } => {
+                let data = base64::decode(base64).map_err(|err| err.to_string())?;
+                (Source::Bytes(data.into()), None, None)
+            }
+            StreamSource::Text { text } => (
+                Source::Bytes(text.into_bytes().into()),
+                Some(media_type!(TEXT / PLAIN; CHARSET = UTF_8).into()),
+                None,
+            ),
+            StreamSource::File { .. } if !self.allow_files => {
+                return Err("File streams are not available on socket connections".into());
+            }
+            StreamSource::File { path } => {
+                let name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned());
+                (Source::File(path), None, name)
+            }
+            StreamSource::Handle { handle } => {
+                let data = self
+                    .handles
+                    .get(&handle)
+                    .ok_or_else(|| format!("Unknown stream handle: {}", handle))?;
+                (
+                    data.source.clone(),
+                    Some(data.media_type.clone()),
+                    data.file_name.clone(),
+                )
+            }
+        };
+        let media_type = match stream.media_type {
+            Some(media_type) => MediaTypeBuf::from_string(media_type.clone())
+                .map_err(|_| format!("Invalid media type: {}", media_type))?,
+            None => default_type.unwrap_or_else(|| media_type!(APPLICATION / OCTET_STREAM).into()),
+        };
+        Ok(StreamData {
+            source,
+            media_type,
+            file_name: stream.file_name.or(default_name),
+        })
+    }
+}
+
+#[derive(Clone)]
+enum Source {
+    Bytes(Arc<[u8]>),
+    File(PathBuf),
+}
+
+struct StreamData {
+    source: Source,
+    media_type: MediaTypeBuf,
+    file_name: Option<String>,
+}
+
+impl StreamData {
+    fn open(&self) -> Result<InputStream, String> {
+        let stream = match &self.source {
+            Source::Bytes(data) => {
+                InputStream::new(Cursor::new(data.clone()), self.media_type.clone())
+            }
+            Source::File(path) => InputStream::new(
+                File::open(path).map_err(|err| format!("{}: {}", path.display(), err))?,
+                self.media_type.clone(),
+            ),
+        };
+        let mut stream = match &self.file_name {
+            Some(name) => stream.set_file_name(name),
+            None => stream,
+        };
+        stream.read_header().map_err(|err| err.to_string())?;
+        Ok(stream)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Stream {
+    #[serde(flatten)]
+    pub source: StreamSource,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_name: Option<String>,
+}
+
+impl From<StreamSource> for Stream {
+    fn from(source: StreamSource) -> Self {
+        Self {
+            source,
+            media_type: None,
+            file_name: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(untagged)]
-pub enum Stream {
+pub enum StreamSource {
     Base64 { base64: String },
+    Text { text: String },
     File { path: PathBuf },
+    Handle { handle: u64 },
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct OpenStreamResponse {
+    pub handle: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ReleaseStreamRequest {
+    pub handle: u64,
 }
 
 #[derive(Default, Serialize, Deserialize)]
@@ -104,16 +601,112 @@ impl From<GetCommandsRequest> for Vec<Token> {
     }
 }
 
+#[derive(Default, Deserialize)]
+struct RequestStreams {
+    #[serde(default)]
+    streams: Option<Vec<Stream>>,
+}
+
+#[derive(Deserialize)]
+struct CancelRequest {
+    id: serde_json::Value,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct GetCommandsResponse {
     pub commands: Vec<CommandArgs>,
 }
 
+#[derive(Default, Serialize, Deserialize)]
+pub struct CompletionsRequest {
+    pub input: String,
+    #[serde(default)]
+    pub pos: Option<usize>,
+    #[serde(default = "default_completions_amount")]
+    pub amount: usize,
+}
+
+fn default_completions_amount() -> usize {
+    20
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CompletionsResponse {
+    pub start: usize,
+    pub candidates: Vec<String>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct SuggestionsRequest {
+    #[serde(default)]
+    pub input: String,
+    #[serde(default)]
+    pub input_tokens: Option<Vec<Token>>,
+    #[serde(default = "default_suggestions_amount")]
+    pub amount: usize,
+}
+
+impl From<SuggestionsRequest> for GetCommandsRequest {
+    fn from(req: SuggestionsRequest) -> Self {
+        Self {
+            input: req.input,
+            input_tokens: req.input_tokens,
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct RandomSuggestionsRequest {
+    #[serde(default = "default_suggestions_amount")]
+    pub amount: usize,
+}
+
+fn default_suggestions_amount() -> usize {
+    5
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SuggestionsResponse {
+    pub suggestions: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct HistoryResponse {
+    pub commands: Vec<CommandArgs>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SkillInfo {
+    pub key: String,
+    pub name: String,
+    pub title: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SkillsResponse {
+    pub skills: Vec<SkillInfo>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SkillExplanation {
+    pub skill: String,
+    pub title: String,
+    pub confidence: f64,
+    pub tokens: Vec<Token>,
+    pub command: Option<CommandArgs>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ExplainResponse {
+    pub tokens: Vec<Token>,
+    pub skills: Vec<SkillExplanation>,
+}
+
 #[derive(Default, Serialize, Deserialize)]
 pub struct RunCommandsRequest {
     pub commands: Vec<CommandArgs>,
-    #[serde(default)]
-    pub user: UserContext,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<UserContext>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -139,7 +732,8 @@ mod tests {
     use super::*;
     use serde_derive::Serialize;
     use serde_json::json;
-    use std::io::Cursor;
+    use std::io::{BufRead, BufReader, Cursor};
+    use std::net::TcpStream;
     use tempfile::NamedTempFile;
 
     #[derive(Serialize)]
@@ -199,9 +793,9 @@ mod tests {
         );
         input.append(&mut serde_json::to_vec(&req).unwrap());
 
-        let streams = vec![Stream::Base64 {
+        let streams = vec![Stream::from(StreamSource::Base64 {
             base64: "SGVsbG8gd29ybGQ=".into(),
-        }];
+        })];
         let req = json_rpc2::Request::new(
             Some(json!(4u32)),
             "set_streams".into(),
@@ -227,7 +821,7 @@ mod tests {
         let mut file = NamedTempFile::new().unwrap();
         writeln!(file, "Hello World?").unwrap();
         let path = file.into_temp_path().keep().unwrap();
-        let streams = vec![Stream::File { path }];
+        let streams = vec![Stream::from(StreamSource::File { path })];
         let req = json_rpc2::Request::new(
             Some(json!(6u32)),
             "set_streams".into(),
@@ -250,6 +844,18 @@ mod tests {
         );
         input.append(&mut serde_json::to_vec(&req).unwrap());
 
+        let command = CompletionsRequest {
+            input: "Hello to md".into(),
+            pos: None,
+            amount: 10,
+        };
+        let req = json_rpc2::Request::new(
+            Some(json!(8u32)),
+            "completions".into(),
+            Some(serde_json::to_value(command).unwrap()),
+        );
+        input.append(&mut serde_json::to_vec(&req).unwrap());
+
         let zuk = Yozuk::builder().build();
         let mut input = Cursor::new(input);
         let mut output = Vec::<u8>::new();
@@ -309,16 +915,354 @@ mod tests {
                         Metadata::docs("https://docs.yozuk.com/docs/skills/digest/").unwrap(),
                     )]),
             ),
+            Response::new(
+                8,
+                CompletionsResponse {
+                    start: 9,
+                    candidates: vec!["md4".into(), "md5".into()],
+                },
+            ),
         ];
 
         let responses = responses
             .into_iter()
-            .map(|res| serde_json::to_string(&res.unwrap()).unwrap())
+            .map(|res| serde_json::to_value(res.unwrap()).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(sorted_responses(&output), responses);
+    }
+
+    fn sorted_responses(output: &[u8]) -> Vec<serde_json::Value> {
+        let mut responses = Deserializer::from_slice(output)
+            .into_iter::<serde_json::Value>()
+            .collect::<serde_json::Result<Vec<_>>>()
+            .unwrap();
+        responses.sort_by_key(|res| res["id"].as_u64());
+        responses
+    }
+
+    fn request<T: serde::Serialize>(id: u32, method: &str, params: T) -> Vec<u8> {
+        let req = json_rpc2::Request::new(
+            Some(json!(id)),
+            method.into(),
+            Some(serde_json::to_value(params).unwrap()),
+        );
+        serde_json::to_vec(&req).unwrap()
+    }
+
+    #[test]
+    fn test_rpc_methods() {
+        let input = [
+            request(1, "skills", json!({})),
+            request(2, "random_suggestions", json!({ "amount": 3 })),
+            request(3, "suggestions", json!({ "input": "uuid", "amount": 1 })),
+            request(4, "explain", json!({ "input": "1 + 1" })),
+            request(
+                5,
+                "run_commands",
+                json!({
+                    "commands": [CommandArgs::new().add_args(["yozuk-skill-digest", "--algorithm", "sha1"])],
+                    "streams": [{ "base64": "SGVsbG8gd29ybGQ=" }],
+                }),
+            ),
+            request(6, "$/cancelRequest", json!({ "id": 100 })),
+        ]
+        .concat();
+
+        let zuk = Yozuk::builder().build();
+        let mut output = Vec::<u8>::new();
+        start_server(zuk, Cursor::new(input), &mut output).unwrap();
+        let responses = sorted_responses(&output);
+        assert_eq!(responses.len(), 5);
+
+        let skills = responses[0]["result"]["skills"].as_array().unwrap();
+        assert!(skills.contains(&json!({
+            "key": "yozuk-skill-uuid",
+            "name": "uuid",
+            "title": "UUID Generator",
+        })));
+        assert_eq!(
+            responses[1]["result"]["suggestions"]
+                .as_array()
+                .unwrap()
+                .len(),
+            3
+        );
+        assert_eq!(
+            responses[2]["result"]["suggestions"]
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let explain = &responses[3]["result"];
+        assert_eq!(explain["tokens"].as_array().unwrap().len(), 3);
+        assert_eq!(explain["skills"][0]["skill"], "yozuk-skill-calc");
+        assert_eq!(explain["skills"][0]["title"], "Calculator");
+        assert_eq!(
+            explain["skills"][0]["command"]["args"],
+            json!(["yozuk-skill-calc", "1+1"])
+        );
+
+        assert_eq!(
+            responses[4],
+            serde_json::to_value(
+                Response::new(
+                    5,
+                    RunCommandsResponse::Ok(vec![Output::new()
+                        .set_title("Digest")
+                        .add_block(
+                            block::Data::new()
+                                .set_text_data("7b502c3a1f48c8609ae212cdfb639dee39673f5e"),
+                        )
+                        .add_metadata(
+                            Metadata::docs("https://docs.yozuk.com/docs/skills/digest/").unwrap(),
+                        )]),
+                )
+                .unwrap()
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_cancel_request() {
+        let input = [
+            request(
+                1,
+                "run_commands",
+                RunCommandsRequest {
+                    commands: vec![CommandArgs::new().add_args(["yozuk-skill-calc", "1+1"])],
+                    ..Default::default()
+                },
+            ),
+            request(2, "$/cancelRequest", json!({ "id": 1 })),
+        ]
+        .concat();
+
+        let zuk = Yozuk::builder().build();
+        let mut output = Vec::<u8>::new();
+        start_server(zuk, Cursor::new(input), &mut output).unwrap();
+
+        // The request either completes or is cancelled, but is answered exactly once.
+        let responses = sorted_responses(&output);
+        assert_eq!(responses.len(), 1);
+        assert_eq!(responses[0]["id"], 1);
+        if let Some(error) = responses[0].get("error") {
+            assert_eq!(error["code"], REQUEST_CANCELLED);
+        }
+    }
+
+    #[test]
+    fn test_pipelined_requests() {
+        let count = MAX_QUEUED_REQUESTS as u32 * 2;
+        let input = (0..count)
+            .flat_map(|id| request(id, "get_commands", json!({ "input": "1 + 1" })))
             .collect::<Vec<_>>();
 
+        let zuk = Yozuk::builder().build();
+        let mut output = Vec::<u8>::new();
+        start_server(zuk, Cursor::new(input), &mut output).unwrap();
+        let responses = sorted_responses(&output);
+        assert_eq!(responses.len(), count as usize);
+        assert!(responses.iter().all(|res| res["result"].is_object()));
+    }
+
+    fn call<S: Read + Write>(stream: &mut BufReader<S>, req: &[u8]) -> serde_json::Value {
+        stream.get_mut().write_all(req).unwrap();
+        let mut line = String::new();
+        stream.read_line(&mut line).unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    #[test]
+    fn test_endpoint() {
         assert_eq!(
-            String::from_utf8(output).unwrap(),
-            responses.join("\n") + "\n"
+            "127.0.0.1:8080".parse::<Endpoint>().unwrap(),
+            Endpoint::Tcp("127.0.0.1:8080".parse().unwrap())
         );
+        assert!("0.0.0.0:8080".parse::<Endpoint>().is_err());
+    }
+
+    #[test]
+    fn test_tcp_sessions() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || Listener::Tcp(listener).serve(Yozuk::builder().build()));
+
+        let mut a = BufReader::new(TcpStream::connect(addr).unwrap());
+        let mut b = BufReader::new(TcpStream::connect(addr).unwrap());
+
+        let streams = vec![Stream::from(StreamSource::Base64 {
+            base64: "SGVsbG8gd29ybGQ=".into(),
+        })];
+        assert_eq!(
+            call(&mut a, &request(1, "set_streams", streams)),
+            json!({ "jsonrpc": "2.0", "id": 1, "result": null })
+        );
+        let user = UserContext {
+            username: Some("alice".into()),
+            ..Default::default()
+        };
+        call(&mut a, &request(2, "set_user_context", user));
+
+        let digest = RunCommandsRequest {
+            commands: vec![CommandArgs::new().add_args([
+                "yozuk-skill-digest",
+                "--algorithm",
+                "sha1",
+            ])],
+            ..Default::default()
+        };
+
+        // The streams set by the other client are not visible.
+        let res = call(&mut b, &request(1, "run_commands", &digest));
+        assert_eq!(res["result"]["result"], "error");
+
+        let res = call(&mut a, &request(3, "run_commands", &digest));
+        assert_eq!(res["result"]["result"], "ok");
+
+        let res = call(&mut a, &request(4, "history", json!({})));
+        assert_eq!(
+            res["result"],
+            serde_json::to_value(HistoryResponse {
+                commands: digest.commands.clone()
+            })
+            .unwrap()
+        );
+        let res = call(&mut b, &request(2, "history", json!({})));
+        assert_eq!(
+            res["result"],
+            serde_json::to_value(HistoryResponse {
+                commands: digest.commands
+            })
+            .unwrap()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unix_socket() {
+        assert_eq!(
+            "unix:/tmp/zuk.sock".parse::<Endpoint>().unwrap(),
+            Endpoint::Unix("/tmp/zuk.sock".into())
+        );
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("zuk.sock");
+        let listener = Endpoint::Unix(path.clone()).bind().unwrap();
+        thread::spawn(move || listener.serve(Yozuk::builder().build()));
+
+        let mut stream = BufReader::new(std::os::unix::net::UnixStream::connect(&path).unwrap());
+        let req = GetCommandsRequest {
+            input: "1 + 1".into(),
+            ..Default::default()
+        };
+        let res = call(&mut stream, &request(1, "get_commands", req));
+        assert_eq!(
+            res["result"]["commands"][0]["args"],
+            json!(["yozuk-skill-calc", "1+1"])
+        );
+        let streams = vec![Stream::from(StreamSource::File {
+            path: "/etc/passwd".into(),
+        })];
+        let res = call(&mut stream, &request(2, "set_streams", streams));
+        assert!(res["error"].is_object());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unix_socket_cleanup() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("zuk.sock");
+        let socket = UnixSocket::bind(&path).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        drop(socket);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_stream_store() {
+        let mut store = StreamStore::default();
+        let stream: Stream = serde_json::from_value(json!({
+            "text": "Hello world",
+            "file_name": "hello.txt",
+        }))
+        .unwrap();
+        let handle = store.open(stream).unwrap();
+
+        let stream: Stream = serde_json::from_value(json!({
+            "handle": handle,
+            "media_type": "text/markdown",
+        }))
+        .unwrap();
+        let streams = store.open_streams(vec![stream.clone(), stream]).unwrap();
+        assert_eq!(
+            streams[0].media_type(),
+            &MediaTypeBuf::from_string("text/markdown".into()).unwrap()
+        );
+        assert_eq!(streams[1].file_name(), Some("hello.txt"));
+
+        let stream: Stream = serde_json::from_value(json!({ "text": "" })).unwrap();
+        let streams = store.open_streams(vec![stream]).unwrap();
+        assert_eq!(
+            streams[0].media_type(),
+            &media_type!(TEXT / PLAIN; CHARSET = UTF_8)
+        );
+
+        let stream: Stream =
+            serde_json::from_value(json!({ "base64": "", "media_type": "?" })).unwrap();
+        assert!(store.open(stream).is_err());
+
+        assert!(store.release(handle));
+        assert!(!store.release(handle));
+        let stream = Stream::from(StreamSource::Handle { handle });
+        assert!(store.open_streams(vec![stream]).is_err());
+
+        let stream = Stream::from(StreamSource::File {
+            path: "Cargo.toml".into(),
+        });
+        assert!(store.open(stream.clone()).is_err());
+        assert!(StreamStore::new(true).open(stream).is_ok());
+
+        let stream = Stream::from(StreamSource::File { path: ".".into() });
+        let err = StreamStore::new(true).open_streams(vec![stream]).err();
+        assert!(err.unwrap().contains("directory"));
+    }
+
+    #[test]
+    fn test_stream_handles() {
+        let digest = |id, handle| {
+            request(
+                id,
+                "run_commands",
+                json!({
+                    "commands": [CommandArgs::new().add_args(["yozuk-skill-digest", "--algorithm", "sha1"])],
+                    "streams": [{ "handle": handle }],
+                }),
+            )
+        };
+        let input = [
+            request(1, "open_stream", json!({ "text": "Hello world" })),
+            digest(2, 1),
+            digest(3, 1),
+            request(4, "release_stream", json!({ "handle": 1 })),
+            digest(5, 1),
+        ]
+        .concat();
+
+        let zuk = Yozuk::builder().build();
+        let mut output = Vec::<u8>::new();
+        start_server(zuk, Cursor::new(input), &mut output).unwrap();
+        let responses = sorted_responses(&output);
+
+        assert_eq!(responses[0]["result"], json!({ "handle": 1 }));
+        assert_eq!(responses[1]["result"], responses[2]["result"]);
+        assert_eq!(responses[1]["result"]["result"], "ok");
+        assert_eq!(responses[3]["result"], true);
+        assert_eq!(responses[4]["error"]["code"], -32602);
     }
 }
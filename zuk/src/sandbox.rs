@@ -0,0 +1,165 @@
+#![cfg(all(target_os = "linux", feature = "landlock"))]
+
+use crate::args::Args;
+use crate::files::is_dir_path;
+use anyhow::Result;
+use landlock::{
+    path_beneath_rules, Access, AccessFs, Ruleset, RulesetAttr, RulesetCreatedAttr, RulesetStatus,
+    ABI,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const ABI_VERSION: ABI = ABI::V3;
+
+const SYSTEM_READ_PATHS: &[&str] = &[
+    "/dev/tty",
+    "/etc/localtime",
+    "/etc/timezone",
+    "/usr/share/zoneinfo",
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct Policy {
+    read: Vec<PathBuf>,
+    write: Vec<PathBuf>,
+}
+
+impl Policy {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn from_args(args: &Args) -> Self {
+        let mut policy = Self::new().allow_read_iter(SYSTEM_READ_PATHS);
+        policy = policy.allow_read_iter(&args.input);
+        if let Some(model) = &args.model {
+            policy = policy.allow_read(model);
+        }
+        if let Some(batch) = args.batch.as_ref().filter(|path| path.as_os_str() != "-") {
+            policy = policy.allow_read(batch);
+        }
+        if let Some(output) = &args.output {
+            policy = policy.allow_write(output);
+        }
+        policy
+    }
+
+    pub fn allow_read<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.read.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    pub fn allow_read_iter<I, P>(self, iter: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        iter.into_iter()
+            .fold(self, |policy, path| policy.allow_read(path))
+    }
+
+    pub fn allow_write<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.write.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    pub fn restrict_self(&self) -> Result<RulesetStatus> {
+        self.create_missing()?;
+        let write =
+            AccessFs::WriteFile | AccessFs::MakeReg | AccessFs::MakeDir | AccessFs::Truncate;
+        let status = Ruleset::default()
+            .handle_access(AccessFs::from_all(ABI_VERSION))?
+            .create()?
+            .add_rules(path_beneath_rules(
+                &self.read,
+                AccessFs::from_read(ABI_VERSION),
+            ))?
+            .add_rules(path_beneath_rules(&self.write, write))?
+            .restrict_self()?;
+        Ok(status.ruleset)
+    }
+
+    // Landlock cannot grant access to a path that does not exist yet,
+    // so missing outputs are created up front instead of opening up their parent.
+    fn create_missing(&self) -> Result<()> {
+        for path in self.write.iter().filter(|path| !path.exists()) {
+            if is_dir_path(path) {
+                fs::create_dir_all(path)?;
+            } else {
+                fs::File::create(path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_restrict_self() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let root = dir.path().to_path_buf();
+        let allowed = root.join("allowed.txt");
+        let forbidden = root.join("forbidden.txt");
+        let output = root.join("out");
+        fs::write(&allowed, "allowed")?;
+        fs::write(&forbidden, "forbidden")?;
+        fs::create_dir(&output)?;
+
+        let policy = Policy::new().allow_read(&allowed).allow_write(&output);
+
+        // Landlock applies to the calling thread only.
+        thread::spawn(move || -> Result<()> {
+            if policy.restrict_self()? == RulesetStatus::NotEnforced {
+                return Ok(());
+            }
+            assert_eq!(fs::read_to_string(&allowed)?, "allowed");
+            assert!(fs::read(&forbidden).is_err());
+            assert!(fs::write(&forbidden, "overwritten").is_err());
+            assert!(fs::write(root.join("new.txt"), "new").is_err());
+
+            fs::write(output.join("result.txt"), "result")?;
+            fs::create_dir(output.join("nested"))?;
+            assert!(fs::read(output.join("result.txt")).is_err());
+            Ok(())
+        })
+        .join()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_restrict_missing_output() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let root = dir.path().to_path_buf();
+        let sibling = root.join("sibling.txt");
+        let output = root.join("out.png");
+        let output_dir = root.join("blocks/");
+        fs::write(&sibling, "sibling")?;
+
+        let policy = Policy::new().allow_write(&output).allow_write(&output_dir);
+
+        let parent = root.clone();
+        thread::spawn(move || -> Result<()> {
+            if policy.restrict_self()? == RulesetStatus::NotEnforced {
+                return Ok(());
+            }
+            fs::write(&output, "result")?;
+            fs::write(output_dir.join("1.txt"), "result")?;
+            assert!(fs::write(&sibling, "overwritten").is_err());
+            assert!(fs::write(parent.join("new.txt"), "new").is_err());
+            assert!(fs::create_dir(parent.join("nested")).is_err());
+            assert!(fs::remove_file(&sibling).is_err());
+            Ok(())
+        })
+        .join()
+        .unwrap()?;
+
+        assert_eq!(fs::read_to_string(root.join("out.png"))?, "result");
+        assert!(root.join("blocks").is_dir());
+        Ok(())
+    }
+}
@@ -0,0 +1,242 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use crate::files;
+use anyhow::{anyhow, bail, Result};
+use std::fs::File;
+use std::io::{Cursor, Write};
+use std::path::PathBuf;
+use yozuk_sdk::prelude::*;
+
+const HELP: &str = "\
+:help                   Show this message
+:load <file>            Attach a file to the next query
+:save <n> <path>        Write the result $n to a file
+:set [tz|locale] [val]  Show or change the user context
+:history                Show previous queries
+:exit                   Exit the REPL";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetaCommand {
+    Help,
+    Load(PathBuf),
+    Save(usize, PathBuf),
+    Set(Option<String>, Option<String>),
+    History,
+    Exit,
+}
+
+impl MetaCommand {
+    pub fn parse(line: &str) -> Option<Result<Self>> {
+        let line = line.trim().strip_prefix(':')?;
+        Some(Self::parse_args(line))
+    }
+
+    fn parse_args(line: &str) -> Result<Self> {
+        let args = shell_words::split(line)?;
+        let args = args.iter().map(|arg| arg.as_str()).collect::<Vec<_>>();
+        match &args[..] {
+            ["help"] => Ok(Self::Help),
+            ["load", file] => Ok(Self::Load(file.into())),
+            ["save", n, path] => Ok(Self::Save(parse_index(n)?, path.into())),
+            ["set"] => Ok(Self::Set(None, None)),
+            ["set", key] => Ok(Self::Set(Some(key.to_string()), None)),
+            ["set", key, value] => Ok(Self::Set(Some(key.to_string()), Some(value.to_string()))),
+            ["history"] => Ok(Self::History),
+            ["exit" | "quit"] => Ok(Self::Exit),
+            [name, ..] => bail!("Unknown command :{}. Type :help for usage.", name),
+            [] => bail!("Type :help for usage."),
+        }
+    }
+}
+
+fn parse_index(s: &str) -> Result<usize> {
+    s.trim_start_matches('$')
+        .parse()
+        .map_err(|_| anyhow!("Invalid result number: {}", s))
+}
+
+pub struct Session {
+    pub user: UserContext,
+    history: Vec<String>,
+    results: Vec<block::Data>,
+    attachments: Vec<PathBuf>,
+}
+
+impl Session {
+    pub fn new(user: UserContext) -> Self {
+        Self {
+            user,
+            history: Vec::new(),
+            results: Vec::new(),
+            attachments: Vec::new(),
+        }
+    }
+
+    pub fn exec<W: Write>(&mut self, command: MetaCommand, mut output: W) -> Result<bool> {
+        match command {
+            MetaCommand::Help => writeln!(output, "{}", HELP)?,
+            MetaCommand::Load(file) => {
+                File::open(&file)?;
+                writeln!(output, "Attached {}", file.display())?;
+                self.attachments.push(file);
+            }
+            MetaCommand::Save(n, path) => {
+                let data = self.result(n)?;
+                for path in files::write_data_blocks(&path, &[data])? {
+                    writeln!(output, "Saved to {}", path.display())?;
+                }
+            }
+            MetaCommand::Set(key, value) => {
+                let entry = match key.as_deref() {
+                    None => {
+                        writeln!(
+                            output,
+                            "tz: {}",
+                            self.user.timezone.as_deref().unwrap_or("")
+                        )?;
+                        writeln!(
+                            output,
+                            "locale: {}",
+                            self.user.locale.as_deref().unwrap_or("")
+                        )?;
+                        return Ok(true);
+                    }
+                    Some("tz" | "timezone") => &mut self.user.timezone,
+                    Some("locale") => &mut self.user.locale,
+                    Some(key) => bail!("Unknown setting: {}", key),
+                };
+                if value.is_some() {
+                    *entry = value;
+                }
+                writeln!(output, "{}", entry.as_deref().unwrap_or(""))?;
+            }
+            MetaCommand::History => {
+                for (i, query) in self.history.iter().enumerate() {
+                    writeln!(output, "{:>4}  {}", i + 1, query)?;
+                }
+            }
+            MetaCommand::Exit => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    pub fn prepare(
+        &mut self,
+        line: &str,
+        tokens: Vec<Token>,
+    ) -> Result<(Vec<Token>, Vec<InputStream>)> {
+        let mut streams = Vec::new();
+        let mut expanded = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            let data = token
+                .as_str()
+                .strip_prefix('$')
+                .filter(|_| token.raw_str.is_none())
+                .and_then(|n| parse_index(n).ok())
+                .and_then(|n| self.result(n).ok());
+            match data {
+                Some(data) => {
+                    if yozuk_helper_filetype::is_utf8_text(&data.data) {
+                        expanded.push(Token {
+                            data: data.data.clone(),
+                            ..token
+                        });
+                    } else {
+                        streams.push(InputStream::new(
+                            Cursor::new(data.data.clone()),
+                            data.media_type.clone(),
+                        ));
+                    }
+                }
+                None => expanded.push(token),
+            }
+        }
+        for file in self.attachments.drain(..) {
+            streams.push(InputStream::new(
+                File::open(file)?,
+                media_type!(APPLICATION / OCTET_STREAM),
+            ));
+        }
+        self.history.push(line.to_string());
+        Ok((expanded, streams))
+    }
+
+    pub fn push_results(&mut self, outputs: &[Output]) -> Vec<usize> {
+        outputs
+            .iter()
+            .flat_map(|output| &output.blocks)
+            .filter_map(|block| match block {
+                Block::Data(data) => {
+                    self.results.push(data.clone());
+                    Some(self.results.len())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn result(&self, n: usize) -> Result<&block::Data> {
+        n.checked_sub(1)
+            .and_then(|index| self.results.get(index))
+            .ok_or_else(|| anyhow!("No such result: ${}", n))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert!(MetaCommand::parse("generate uuid").is_none());
+        assert_eq!(
+            MetaCommand::parse(":help").unwrap().unwrap(),
+            MetaCommand::Help
+        );
+        assert_eq!(
+            MetaCommand::parse(" :save $2 'my file.png'")
+                .unwrap()
+                .unwrap(),
+            MetaCommand::Save(2, "my file.png".into())
+        );
+        assert_eq!(
+            MetaCommand::parse(":set tz Asia/Tokyo").unwrap().unwrap(),
+            MetaCommand::Set(Some("tz".into()), Some("Asia/Tokyo".into()))
+        );
+        assert!(MetaCommand::parse(":save x y").unwrap().is_err());
+        assert!(MetaCommand::parse(":unknown").unwrap().is_err());
+    }
+
+    #[test]
+    fn test_session() -> Result<()> {
+        let mut session = Session::new(Default::default());
+        let mut out = Vec::new();
+        session.exec(
+            MetaCommand::Set(Some("tz".into()), Some("UTC".into())),
+            &mut out,
+        )?;
+        assert_eq!(session.user.timezone.as_deref(), Some("UTC"));
+
+        let outputs = [Output::new()
+            .add_block(block::Comment::new().set_text("comment"))
+            .add_block(block::Data::new().set_text_data("hello"))
+            .add_block(block::Data::new().set_data(vec![0xff, 0x00]))];
+        assert_eq!(session.push_results(&outputs), [1, 2]);
+
+        let (tokens, streams) = session.prepare("base64 $1 $2", tk!(["base64", "$1", "$2"]))?;
+        assert_eq!(tokens, tk!(["base64", "hello"]));
+        assert_eq!(streams.len(), 1);
+        let (tokens, streams) = session.prepare("echo $3", tk!(["echo", "$3"]))?;
+        assert_eq!(tokens, tk!(["echo", "$3"]));
+        assert!(streams.is_empty());
+
+        out.clear();
+        session.exec(MetaCommand::History, &mut out)?;
+        assert_eq!(
+            String::from_utf8(out)?,
+            "   1  base64 $1 $2\n   2  echo $3\n"
+        );
+        assert!(!session.exec(MetaCommand::Exit, std::io::sink())?);
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use color_quant::NeuQuant;
+use image::RgbaImage;
+use std::io::{self, Write};
+
+const MAX_COLORS: usize = 256;
+const SAMPLE_FACTOR: i32 = 10;
+const BAND_HEIGHT: u32 = 6;
+
+pub fn encode<W: Write>(image: &RgbaImage, mut output: W) -> io::Result<()> {
+    let (width, height) = image.dimensions();
+    let quant = NeuQuant::new(SAMPLE_FACTOR, MAX_COLORS, image.as_raw());
+    let indices = image
+        .pixels()
+        .map(|pixel| (pixel[3] >= 128).then(|| quant.index_of(&pixel.0)))
+        .collect::<Vec<_>>();
+
+    write!(output, "\x1bP0;1;0q\"1;1;{};{}", width, height)?;
+    for (index, rgb) in quant.color_map_rgb().chunks(3).enumerate() {
+        write!(
+            output,
+            "#{};2;{};{};{}",
+            index,
+            percent(rgb[0]),
+            percent(rgb[1]),
+            percent(rgb[2])
+        )?;
+    }
+
+    for top in (0..height).step_by(BAND_HEIGHT as usize) {
+        let rows = (height - top).min(BAND_HEIGHT);
+        let band = &indices[(top * width) as usize..((top + rows) * width) as usize];
+        let mut colors = band.iter().flatten().copied().collect::<Vec<_>>();
+        colors.sort_unstable();
+        colors.dedup();
+
+        for (n, color) in colors.into_iter().enumerate() {
+            if n > 0 {
+                output.write_all(b"$")?;
+            }
+            write!(output, "#{}", color)?;
+
+            let mut run = (0, 0);
+            for x in 0..width as usize {
+                let bits = (0..rows as usize)
+                    .filter(|y| band[y * width as usize + x] == Some(color))
+                    .fold(0, |acc, y| acc | 1 << y);
+                let ch = b'?' + bits;
+                if run.1 > 0 && run.0 != ch {
+                    write_run(&mut output, run)?;
+                    run.1 = 0;
+                }
+                run = (ch, run.1 + 1);
+            }
+            if run.0 != b'?' {
+                write_run(&mut output, run)?;
+            }
+        }
+        output.write_all(b"-")?;
+    }
+
+    output.write_all(b"\x1b\\")?;
+    output.flush()
+}
+
+fn percent(value: u8) -> u32 {
+    (value as u32 * 100 + 127) / 255
+}
+
+fn write_run<W: Write>(mut output: W, (ch, len): (u8, usize)) -> io::Result<()> {
+    if len > 3 {
+        write!(output, "!{}{}", len, ch as char)
+    } else {
+        output.write_all(&vec![ch; len])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn test_encode() {
+        let image = RgbaImage::from_fn(8, 7, |x, _| {
+            if x < 4 {
+                Rgba([0, 0, 0, 255])
+            } else {
+                Rgba([255, 255, 255, 255])
+            }
+        });
+        let mut output = Vec::new();
+        encode(&image, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.starts_with("\x1bP0;1;0q\"1;1;8;7#0;2;"));
+        assert!(output.ends_with("-\x1b\\"));
+        assert!(output.contains("!4~"));
+        assert!(output.contains("!4@"));
+        assert_eq!(output.matches('-').count(), 2);
+    }
+}
@@ -30,23 +30,134 @@ mod term_is_tty {
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-mod term_kitty_image {
-    use anyhow::Result;
+mod term_query {
+    use anyhow::{bail, Result};
     use crossterm::{
         cursor::{RestorePosition, SavePosition},
         terminal::{disable_raw_mode, enable_raw_mode},
         QueueableCommand,
     };
-    use std::io::{stdin, stdout, Read, Write};
+    use std::io::{stdout, Read, Write};
+    use std::time::{Duration, Instant};
+
+    const SUFFIX: &[u8] = b"\x1b[0n";
+    const TIMEOUT: Duration = Duration::from_millis(500);
+
+    struct RawMode;
+
+    impl RawMode {
+        fn enable() -> Result<Self> {
+            enable_raw_mode()?;
+            Ok(Self)
+        }
+    }
+
+    impl Drop for RawMode {
+        fn drop(&mut self) {
+            let _ = disable_raw_mode();
+        }
+    }
+
+    /// Sends `query` to the terminal and reads the response up to the device status report.
+    pub fn query_terminal(query: &[u8]) -> Result<Vec<u8>> {
+        let mut input = Input::open()?;
+        let mut stdout = stdout().lock();
+        let _raw_mode = RawMode::enable()?;
+
+        stdout.queue(SavePosition)?;
+        stdout.write_all(query)?;
+        stdout.queue(RestorePosition)?;
+        stdout.flush()?;
+
+        let deadline = Instant::now() + TIMEOUT;
+        let mut buf = Vec::new();
+        while !buf.ends_with(SUFFIX) {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            if timeout.is_zero() || !input.wait(timeout) {
+                bail!("The terminal did not respond");
+            }
+            let mut chunk = [0; 128];
+            let n = input.read(&mut chunk)?;
+            if n == 0 {
+                bail!("Unexpected end of input");
+            }
+            buf.extend_from_slice(&chunk[..n]);
+        }
+        Ok(buf)
+    }
+
+    // Reads /dev/tty directly so that buffered stdin does not hide pending bytes from poll(2).
+    #[cfg(unix)]
+    struct Input(std::fs::File);
+
+    #[cfg(unix)]
+    impl Input {
+        fn open() -> Result<Self> {
+            Ok(Self(std::fs::File::open("/dev/tty")?))
+        }
+
+        fn wait(&self, timeout: Duration) -> bool {
+            use rustix::io::{poll, PollFd, PollFlags};
+            let mut fds = [PollFd::new(&self.0, PollFlags::IN)];
+            let timeout = timeout.as_millis().try_into().unwrap_or(i32::MAX);
+            poll(&mut fds, timeout).is_ok_and(|n| n > 0)
+        }
+
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    #[cfg(not(unix))]
+    struct Input(std::io::Stdin);
+
+    #[cfg(not(unix))]
+    impl Input {
+        fn open() -> Result<Self> {
+            Ok(Self(std::io::stdin()))
+        }
+
+        fn wait(&self, _timeout: Duration) -> bool {
+            true
+        }
+
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+use term_query::query_terminal;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod term_kitty_image {
+    use super::query_terminal;
+    use anyhow::Result;
+    use image::{ImageFormat, ImageOutputFormat};
+    use std::io::{stdout, Cursor, Write};
 
     const KITTY_QUERY: &[u8] = b"\x1b_Gi=31,s=1,v=1,a=q,t=d,f=24;AAAA\x1b\\\x1b[5n";
     const OK: &str = ";OK\x1b\\";
-    const SUFFIX: &[u8] = b"\x1b[0n";
 
     pub fn is_kitty_image_supported() -> bool {
         is_kitty_image_supported_impl().unwrap_or(false)
     }
 
+    pub fn kitty_image_show(data: &[u8]) -> Result<bool> {
+        if image::guess_format(data).ok() == Some(ImageFormat::Png) {
+            kitty_image_show_png(data)?;
+            return Ok(true);
+        }
+        if let Ok(image) = image::load_from_memory(data) {
+            let mut png = Cursor::new(Vec::new());
+            image.write_to(&mut png, ImageOutputFormat::Png)?;
+            kitty_image_show_png(png.get_ref())?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
     pub fn kitty_image_show_png(data: &[u8]) -> std::io::Result<()> {
         let mut stdout = stdout().lock();
         let data = base64::encode(data);
@@ -69,30 +180,7 @@ mod term_kitty_image {
     }
 
     fn is_kitty_image_supported_impl() -> Result<bool> {
-        let mut stdout = stdout().lock();
-        let mut stdin = stdin().lock();
-
-        enable_raw_mode()?;
-
-        stdout.queue(SavePosition)?;
-        stdout.write_all(KITTY_QUERY)?;
-        stdout.queue(RestorePosition)?;
-        stdout.flush()?;
-
-        let mut buf = vec![0u8; 0];
-        loop {
-            let len = buf.len();
-            buf.resize(len + 128, 0);
-
-            let n = stdin.read(&mut buf[len..])?;
-            buf.resize(len + n, 0);
-
-            if buf.iter().rev().take(SUFFIX.len()).eq(SUFFIX.iter().rev()) {
-                break;
-            }
-        }
-
-        disable_raw_mode()?;
+        let buf = query_terminal(KITTY_QUERY)?;
 
         Ok(String::from_utf8(buf)
             .ok()
@@ -107,26 +195,102 @@ mod term_kitty_image {
         false
     }
 
-    pub fn kitty_image_show_png(_data: &[u8]) -> std::io::Result<()> {
-        Ok(())
+    pub fn kitty_image_show(_data: &[u8]) -> anyhow::Result<bool> {
+        Ok(false)
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+mod term_sixel_image {
+    use super::query_terminal;
+    use crate::sixel;
+    use anyhow::Result;
+    use image::imageops::FilterType;
+    use std::io::{stdout, Write};
+
+    const DA1_QUERY: &[u8] = b"\x1b[c\x1b[5n";
+    const PREFIX: &str = "\x1b[?";
+    const SIXEL_ATTRIBUTE: &str = "4";
+    const MAX_SIZE: u32 = 1024;
+
+    pub fn is_sixel_supported() -> bool {
+        is_sixel_supported_impl().unwrap_or(false)
+    }
+
+    pub fn sixel_image_show(data: &[u8]) -> Result<bool> {
+        let image = if let Ok(image) = image::load_from_memory(data) {
+            image
+        } else {
+            return Ok(false);
+        };
+        let image = if image.width() > MAX_SIZE || image.height() > MAX_SIZE {
+            image.resize(MAX_SIZE, MAX_SIZE, FilterType::Lanczos3)
+        } else {
+            image
+        };
+
+        let mut stdout = stdout().lock();
+        sixel::encode(&image.to_rgba8(), &mut stdout)?;
+        writeln!(stdout)?;
+        Ok(true)
+    }
+
+    fn is_sixel_supported_impl() -> Result<bool> {
+        let buf = query_terminal(DA1_QUERY)?;
+
+        let response = String::from_utf8(buf)?;
+        Ok(response
+            .split(PREFIX)
+            .nth(1)
+            .and_then(|attrs| attrs.split('c').next())
+            .is_some_and(|attrs| attrs.split(';').any(|attr| attr == SIXEL_ATTRIBUTE)))
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod term_sixel_image {
+    pub fn is_sixel_supported() -> bool {
+        false
+    }
+
+    pub fn sixel_image_show(_data: &[u8]) -> anyhow::Result<bool> {
+        Ok(false)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageProtocol {
+    Iterm2,
+    Kitty,
+    Sixel,
+    Blocks,
+}
+
+pub fn image_protocol() -> ImageProtocol {
+    static PROTOCOL: std::sync::OnceLock<ImageProtocol> = std::sync::OnceLock::new();
+    *PROTOCOL.get_or_init(|| {
+        if is_iterm2_image_supported() {
+            ImageProtocol::Iterm2
+        } else if is_kitty_image_supported() {
+            ImageProtocol::Kitty
+        } else if is_sixel_supported() {
+            ImageProtocol::Sixel
+        } else {
+            ImageProtocol::Blocks
+        }
+    })
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 mod term_iterm2_image {
+    use super::query_terminal;
     use anyhow::Result;
     use base64::write::EncoderWriter;
-    use crossterm::{
-        cursor::{RestorePosition, SavePosition},
-        terminal::{disable_raw_mode, enable_raw_mode},
-        QueueableCommand,
-    };
     use semver::{Version, VersionReq};
-    use std::io::{stdin, stdout, Read, Write};
+    use std::io::{stdout, Write};
 
     const ITERM2_QUERY: &[u8] = b"\x1b[1337n\x1b[5n";
     const PREFIX: &str = "\x1b[ITERM2 ";
-    const SUFFIX: &[u8] = b"\x1b[0n";
 
     pub fn is_iterm2_image_supported() -> bool {
         let req = VersionReq::parse(">=2.9").unwrap();
@@ -137,30 +301,7 @@ mod term_iterm2_image {
     }
 
     fn iterm2_version() -> Result<Version> {
-        let mut stdout = stdout().lock();
-        let mut stdin = stdin().lock();
-
-        enable_raw_mode()?;
-
-        stdout.queue(SavePosition)?;
-        stdout.write_all(ITERM2_QUERY)?;
-        stdout.queue(RestorePosition)?;
-        stdout.flush()?;
-
-        let mut buf = vec![0u8; 0];
-        loop {
-            let len = buf.len();
-            buf.resize(len + 128, 0);
-
-            let n = stdin.read(&mut buf[len..])?;
-            buf.resize(len + n, 0);
-
-            if buf.iter().rev().take(SUFFIX.len()).eq(SUFFIX.iter().rev()) {
-                break;
-            }
-        }
-
-        disable_raw_mode()?;
+        let buf = query_terminal(ITERM2_QUERY)?;
 
         let version = String::from_utf8(buf)?;
         let version = version
@@ -203,3 +344,4 @@ mod term_iterm2_image {
 pub use term_is_tty::*;
 pub use term_iterm2_image::*;
 pub use term_kitty_image::*;
+pub use term_sixel_image::*;